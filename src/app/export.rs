@@ -0,0 +1,298 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::app::App;
+use crate::app::report::csv_field;
+use crate::command::GoalKind;
+use crate::habit::HabitWrapper;
+use crate::ics;
+use crate::utils;
+
+/// What `dijo export` writes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// A header line, then a line per habit and day.
+    Csv,
+    /// A JSON object per habit and day, one per line.
+    Jsonl,
+    /// An iCalendar file with an all-day event per day a goal was reached.
+    Ics,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::Jsonl),
+            "ics" => Ok(ExportFormat::Ics),
+            _ => Err(format!("`{s}` is not csv, jsonl or ics")),
+        }
+    }
+}
+
+/// One habit on one day. Days counted as reached without an entry, like an
+/// addiction's clean days, have no value.
+#[derive(Debug, Serialize)]
+struct Record {
+    habit: String,
+    date: NaiveDate,
+    kind: &'static str,
+    /// Written the way `:set` reads it, e.g. `2.5` or `1h30`.
+    value: Option<String>,
+    /// The goal in force that day, or the limit for an addiction; `None` for
+    /// kinds that have no amount to reach.
+    goal: Option<String>,
+    period: String,
+    reached: bool,
+}
+
+/// Every recorded or reached day of the exported habits, by habit and date.
+#[derive(Debug)]
+pub struct Export {
+    records: Vec<Record>,
+}
+
+impl App {
+    /// The days of habit `name`, or of every habit, retired ones included,
+    /// from `from` up to `to`; either end left out is open. `archived` holds
+    /// the habits of the archive files, as `utils::load_archived_habits`
+    /// reads them, whose months are exported alongside the live ones.
+    pub fn export(
+        &self,
+        archived: &[Value],
+        name: Option<&str>,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<Export, String> {
+        let all = self.habits.iter().chain(self.retired.iter());
+        let habits: Vec<&dyn HabitWrapper> = match name {
+            Some(name) => {
+                let habit = all
+                    .clone()
+                    .find(|h| h.name() == name)
+                    .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+                vec![habit.as_ref()]
+            }
+            None => all.map(|h| h.as_ref()).collect(),
+        };
+        let in_range = |d: &NaiveDate| from.is_none_or(|f| *d >= f) && to.is_none_or(|t| *d <= t);
+        let mut records = Vec::new();
+        for habit in habits {
+            let mut rows = Vec::new();
+            // months that can't be read back as a habit still give the days
+            // they reached the goal on
+            let mut unreadable = BTreeSet::new();
+            for month in archived.iter().filter(|h| h["name"] == habit.name()) {
+                let reached = utils::archived_reached_dates(month).into_iter().collect();
+                match serde_json::from_value::<Box<dyn HabitWrapper>>(month.clone()) {
+                    Ok(month) => rows.extend(habit_records(month.as_ref(), &reached, in_range)),
+                    Err(_) => unreadable.extend(reached),
+                }
+            }
+            let mut reached: BTreeSet<NaiveDate> = habit.reached_dates().into_iter().collect();
+            reached.extend(unreadable);
+            rows.extend(habit_records(habit, &reached, in_range));
+            rows.sort_by_key(|r| r.date);
+            rows.dedup_by_key(|r| r.date);
+            records.extend(rows);
+        }
+        Ok(Export { records })
+    }
+}
+
+/// A record for each day `habit` has an entry or `reached` its goal on.
+fn habit_records(
+    habit: &dyn HabitWrapper,
+    reached: &BTreeSet<NaiveDate>,
+    in_range: impl Fn(&NaiveDate) -> bool,
+) -> Vec<Record> {
+    let kind = habit.kind();
+    let dates: BTreeSet<NaiveDate> = (habit.get_dates().into_iter())
+        .chain(reached.iter().copied())
+        .filter(in_range)
+        .collect();
+    dates
+        .into_iter()
+        .map(|date| Record {
+            habit: habit.name().to_owned(),
+            date,
+            kind: kind_name(&kind),
+            value: habit.entry_value(date),
+            goal: match kind {
+                GoalKind::Bit | GoalKind::Cycle(_) | GoalKind::Rating(..) => None,
+                GoalKind::Addiction(_) => habit.limit_at(date).map(|l| habit.format_value(l)),
+                _ => Some(habit.format_value(habit.goal_at(date))),
+            },
+            period: habit.period_at(date).to_string(),
+            reached: reached.contains(&date),
+        })
+        .collect()
+}
+
+fn kind_name(kind: &GoalKind) -> &'static str {
+    match kind {
+        GoalKind::Count(_) => "count",
+        GoalKind::Bit => "bit",
+        GoalKind::Float(..) => "float",
+        GoalKind::Addiction(_) => "addiction",
+        GoalKind::Duration(_) => "duration",
+        GoalKind::Cycle(_) => "cycle",
+        GoalKind::Rating(..) => "rating",
+    }
+}
+
+impl Export {
+    /// The whole export in `format`, ending in a line break.
+    pub fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Csv => {
+                let mut out = String::from("habit,date,kind,value,goal,period,reached\n");
+                for r in &self.records {
+                    out.push_str(&format!(
+                        "{},{},{},{},{},{},{}\n",
+                        csv_field(&r.habit),
+                        r.date,
+                        r.kind,
+                        csv_field(r.value.as_deref().unwrap_or_default()),
+                        r.goal.as_deref().unwrap_or_default(),
+                        r.period,
+                        r.reached
+                    ));
+                }
+                out
+            }
+            ExportFormat::Jsonl => self
+                .records
+                .iter()
+                .filter_map(|r| serde_json::to_string(r).ok())
+                .map(|line| line + "\n")
+                .collect(),
+            ExportFormat::Ics => {
+                ics::write_all_day_events(self.records.iter().filter(|r| r.reached).map(|r| {
+                    let summary = match &r.value {
+                        Some(value) => format!("{}: {value}", r.habit),
+                        None => r.habit.clone(),
+                    };
+                    (r.date, r.habit.as_str(), summary)
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::habit::{Addiction, Bit, Count, Duration, GoalPeriod, Habit, TrackEvent};
+    use serde_json::json;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 2)));
+        app.add_habit(Box::new(Duration::new("read", 30)));
+        app.habits[0].modify(d(2), TrackEvent::Set(1));
+        app.habits[0].modify(d(3), TrackEvent::Set(2));
+        app.habits[1].modify(d(2), TrackEvent::Set(90));
+        let mut floss = Bit::new("floss, teeth");
+        Habit::modify(&mut floss, d(3), TrackEvent::Increment);
+        app.retired.push(Box::new(floss));
+        app
+    }
+
+    /// December of `gym`, as the archive file holds it, and a November whose
+    /// goal can't be read back.
+    fn archived() -> Vec<Value> {
+        let dec = |day| NaiveDate::from_ymd_opt(2023, 12, day).unwrap();
+        let mut gym: Box<dyn HabitWrapper> = Box::new(Count::new("gym", 2));
+        gym.modify(dec(30), TrackEvent::Set(1));
+        gym.modify(dec(31), TrackEvent::Set(3));
+        vec![
+            serde_json::to_value(&gym).unwrap(),
+            json!({"type": "Count", "name": "gym", "goal": "two", "stats": {"2023-11-05": 3}}),
+        ]
+    }
+
+    #[test]
+    fn csv_has_a_row_per_habit_and_day_archived_and_retired_included() {
+        let csv = app()
+            .export(&archived(), None, None, None)
+            .unwrap()
+            .render(ExportFormat::Csv);
+        assert_eq!(
+            csv,
+            "habit,date,kind,value,goal,period,reached\n\
+             gym,2023-11-05,count,,2,day,true\n\
+             gym,2023-12-30,count,1,2,day,false\n\
+             gym,2023-12-31,count,3,2,day,true\n\
+             gym,2024-01-02,count,1,2,day,false\n\
+             gym,2024-01-03,count,2,2,day,true\n\
+             read,2024-01-02,duration,1h30,30m,day,true\n\
+             \"floss, teeth\",2024-01-03,bit,yes,,day,true\n"
+        );
+    }
+
+    #[test]
+    fn habit_and_dates_narrow_the_export() {
+        let export = app()
+            .export(&archived(), Some("gym"), Some(d(2)), Some(d(2)))
+            .unwrap();
+        let jsonl = export.render(ExportFormat::Jsonl);
+        let lines: Vec<Value> = jsonl
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["date"], "2024-01-02");
+        assert_eq!(lines[0]["value"], "1");
+        assert_eq!(lines[0]["reached"], false);
+
+        assert!(app().export(&[], Some("swim"), None, None).is_err());
+    }
+
+    #[test]
+    fn addictions_export_the_limit_of_each_day() {
+        let mut app = App::new();
+        let mut coffee = Addiction::new("coffee", 2);
+        Habit::insert_entry(&mut coffee, d(1), 2);
+        Habit::insert_entry(&mut coffee, d(3), 2);
+        Habit::set_goal(&mut coffee, GoalKind::Addiction(1), GoalPeriod::Daily, d(3)).unwrap();
+        app.add_habit(Box::new(coffee));
+        let export = app.export(&[], None, Some(d(1)), Some(d(3))).unwrap();
+        let csv = export.render(ExportFormat::Csv);
+        assert!(
+            csv.contains("coffee,2024-01-01,addiction,2,2,day,true\n"),
+            "{csv}"
+        );
+        assert!(
+            csv.contains("coffee,2024-01-03,addiction,2,1,day,false\n"),
+            "{csv}"
+        );
+    }
+
+    #[test]
+    fn ics_has_an_event_per_reached_day() {
+        let ics = app()
+            .export(&archived(), None, None, None)
+            .unwrap()
+            .render(ExportFormat::Ics);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 5);
+        assert!(ics.contains("SUMMARY:gym\r\n"));
+        assert!(ics.contains("SUMMARY:gym: 3\r\n"));
+        assert!(ics.contains("SUMMARY:read: 1h30\r\n"));
+        assert!(
+            !ics.contains(
+                "DTSTART;VALUE=DATE:20240102\r\nDTEND;VALUE=DATE:20240103\r\nSUMMARY:gym"
+            )
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use chrono::{Datelike, Days, Local, NaiveDate};
 use cursive::Vec2;
@@ -11,19 +11,29 @@ use cursive::utils::markup::StyledString;
 
 use crate::CONFIGURATION;
 use crate::command::{Command, CommandLineError, GoalKind};
-use crate::habit::{Bit, Count, Float, GoalPeriod, HabitWrapper, ViewMode};
-use crate::stats::habit_stats;
+use crate::habit::{
+    ActiveRange, Addiction, Bit, Count, Cycle, Duration, Float, GoalPeriod, HabitWrapper, Minutes,
+    Pause, Rating, TrackEvent, ViewMode, convert,
+};
+use crate::ics;
+use crate::stats::RatingStats;
 use crate::utils::{self, GRID_WIDTH, VIEW_HEIGHT, VIEW_WIDTH};
 
-use crate::app::{App, Cursor, Message, MessageKind, StatusLine};
+use crate::app::{
+    App, Change, Cursor, History, Message, MessageKind, SortKey, StatusLine, edited_habit,
+};
 
 impl App {
     pub fn new() -> Self {
         App {
             habits: vec![],
+            retired: vec![],
             focus: 0,
             cursor: Cursor::new(),
             message: Message::startup(),
+            filter: None,
+            sort: None,
+            history: History::default(),
         }
     }
 
@@ -35,6 +45,50 @@ impl App {
         self.habits.iter().map(|x| x.name().to_owned()).collect()
     }
 
+    pub fn list_retired(&self) -> Vec<String> {
+        self.retired.iter().map(|x| x.name().to_owned()).collect()
+    }
+
+    /// Whether any habit, retired ones included, is called `name`.
+    fn name_taken(&self, name: &str) -> bool {
+        self.habits
+            .iter()
+            .chain(&self.retired)
+            .any(|h| h.name() == name)
+    }
+
+    /// Take habit `name` off the grid, keeping its history.
+    pub fn retire_habit(&mut self, name: &str) -> Result<(), String> {
+        let index = self
+            .habits
+            .iter()
+            .position(|h| h.name() == name)
+            .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+        self.retired.push(self.habits.remove(index));
+        if self.focus > index {
+            self.focus -= 1;
+        }
+        self.focus = self.focus.min(self.habits.len().saturating_sub(1));
+        self.focus_visible();
+        Ok(())
+    }
+
+    /// Put retired habit `name` back on the grid, last, or where a kept sort
+    /// order places it.
+    pub fn unretire_habit(&mut self, name: &str) -> Result<(), String> {
+        let index = self
+            .retired
+            .iter()
+            .position(|h| h.name() == name)
+            .ok_or_else(|| format!("No retired habit `{name}`"))?;
+        let habit = self.retired.remove(index);
+        self.habits.push(habit);
+        if let Some(key) = self.sort {
+            self.sort_habits(key, true);
+        }
+        Ok(())
+    }
+
     pub fn missed_habits_by_name(&self, name: &str) -> Vec<String> {
         let target_habit = self.habits.iter().find(|x| x.name() == name);
 
@@ -46,11 +100,276 @@ impl App {
     }
 
     pub fn delete_by_name(&mut self, name: &str) {
-        let old_len = self.habits.len();
-        self.habits.retain(|h| h.name() != name);
-        if old_len == self.habits.len() {
+        let found = match self.habits.iter().position(|h| h.name() == name) {
+            Some(index) => Some((self.habits.remove(index), index, false)),
+            None => (self.retired.iter())
+                .position(|h| h.name() == name)
+                .map(|index| (self.retired.remove(index), index, true)),
+        };
+        match found {
+            Some((habit, index, retired)) => {
+                let change = Change::Removed {
+                    habit,
+                    index,
+                    retired,
+                };
+                self.record(format!("delete `{name}`"), change);
+            }
+            None => {
+                self.message.set_kind(MessageKind::Error);
+                self.message
+                    .set_message(format!("Could not delete habit `{name}`"))
+            }
+        }
+    }
+
+    /// Rename habit `old` to `new`, and in every archived month at once. The
+    /// habit file is left to be saved like any other edit.
+    pub fn rename_habit(&mut self, old: &str, new: &str) -> Result<(), String> {
+        self.rename_habit_in(&utils::archive_dir()?, old, new)
+    }
+
+    /// `rename_habit` with the archived months in `archive`. The archives are
+    /// rewritten first, so a failure leaves the habit as it was.
+    fn rename_habit_in(&mut self, archive: &Path, old: &str, new: &str) -> Result<(), String> {
+        // `all` already means every habit to `:pause`
+        if new == "all" {
+            return Err("`all` is reserved, pick another name".into());
+        }
+        if self.name_taken(new) {
+            return Err(format!("Habit `{new}` already exist"));
+        }
+        // a habit that lives on only in the archives would take on the
+        // renamed one's months
+        if (utils::load_archived_habits_in(archive).iter()).any(|h| h["name"] == new) {
+            return Err(format!("Habit `{new}` already exist in the archive"));
+        }
+        let habit = self
+            .habits
+            .iter_mut()
+            .chain(self.retired.iter_mut())
+            .find(|h| h.name() == old)
+            .ok_or_else(|| format!("Habit `{old}` does not exist"))?;
+        if let Err(e) = utils::rename_archived(archive, old, new) {
+            // put back whichever months were already rewritten
+            let _ = utils::rename_archived(archive, new, old);
+            return Err(e);
+        }
+        habit.set_name(new.to_owned());
+        Ok(())
+    }
+
+    /// Move habit `name` to `position`, counting from 1; positions past the
+    /// end move it last. A manual move replaces any remembered sort order.
+    pub fn move_habit(&mut self, name: &str, position: usize) -> Result<(), String> {
+        let from = self
+            .habits
+            .iter()
+            .position(|h| h.name() == name)
+            .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+        let to = position.clamp(1, self.habits.len()) - 1;
+        self.keep_focus(|habits| {
+            let habit = habits.remove(from);
+            habits.insert(to, habit);
+        });
+        self.sort = None;
+        Ok(())
+    }
+
+    /// Swap the focused habit with the one drawn left or right of it.
+    pub fn swap_focused(&mut self, d: Absolute) {
+        let shown: Vec<usize> = self.rows().concat();
+        let Some(pos) = shown.iter().position(|&i| i == self.focus) else {
+            return;
+        };
+        let neighbour = match d {
+            Absolute::Left => pos.checked_sub(1).and_then(|p| shown.get(p)),
+            Absolute::Right => shown.get(pos + 1),
+            _ => None,
+        };
+        if let Some(&other) = neighbour {
+            self.habits.swap(self.focus, other);
+            self.focus = other;
+            self.sort = None;
+        }
+    }
+
+    /// Order habits by `key`. With `keep`, the order is also saved and
+    /// applied again every time dijo starts.
+    pub fn sort_habits(&mut self, key: SortKey, keep: bool) {
+        let today = Local::now().date_naive();
+        self.keep_focus(|habits| match key {
+            SortKey::Name => habits.sort_by_cached_key(|h| h.name().to_lowercase()),
+            SortKey::Remaining => habits.sort_by_cached_key(|h| {
+                std::cmp::Reverse(if h.is_due(today) {
+                    h.remaining(today)
+                } else {
+                    0
+                })
+            }),
+            SortKey::Streak => {
+                habits.sort_by_cached_key(|h| std::cmp::Reverse(h.stats(today).current_streak))
+            }
+            SortKey::Group => habits
+                .sort_by_cached_key(|h| (h.group().is_none(), h.group().map(str::to_lowercase))),
+        });
+        if keep {
+            self.sort = Some(key);
+        }
+    }
+
+    /// Reorder `habits` with `f`, keeping focus on the same habit.
+    fn keep_focus(&mut self, f: impl FnOnce(&mut Vec<Box<dyn HabitWrapper>>)) {
+        let focused = self.habits.get(self.focus).map(|h| h.name().to_owned());
+        f(&mut self.habits);
+        if let Some(name) = focused {
+            self.focus = self
+                .habits
+                .iter()
+                .position(|h| h.name() == name)
+                .unwrap_or(0);
+        }
+    }
+
+    /// Add `pauses` to the habit called `target`, or to every habit for
+    /// `all`. Reports an unknown habit on the message line and returns
+    /// whether any habit was paused.
+    pub fn pause_habits(&mut self, target: &str, pauses: &[Pause]) -> bool {
+        self.for_each_target(target, |habit| {
+            for &pause in pauses {
+                habit.add_pause(pause);
+            }
+        })
+    }
+
+    /// Unpause the days of `span` for the habit called `target`, or for
+    /// every habit with `all`; without a span every pause is removed.
+    pub fn unpause_habits(&mut self, target: &str, span: Option<Pause>) -> bool {
+        let span = span.unwrap_or(Pause::new(NaiveDate::MIN, NaiveDate::MAX));
+        self.for_each_target(target, |habit| habit.remove_pause(span))
+    }
+
+    /// Apply `f` to the habit called `target`, or to every habit for `all`,
+    /// reporting an unknown habit on the message line.
+    fn for_each_target(
+        &mut self,
+        target: &str,
+        mut f: impl FnMut(&mut Box<dyn HabitWrapper>),
+    ) -> bool {
+        let mut found = false;
+        for habit in self
+            .habits
+            .iter_mut()
+            .filter(|h| target == "all" || h.name() == target)
+        {
+            found = true;
+            f(habit);
+        }
+        if !found {
+            self.message.set_kind(MessageKind::Error);
             self.message
-                .set_message(format!("Could not delete habit `{name}`"))
+                .set_message(format!("Habit `{target}` does not exist"));
+        }
+        found
+    }
+
+    /// Group names in the order their first habit appears.
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups: Vec<&str> = Vec::new();
+        for group in self.habits.iter().filter_map(|h| h.group()) {
+            if !groups.contains(&group) {
+                groups.push(group);
+            }
+        }
+        groups
+    }
+
+    /// The habits on screen, section by section: a header naming the group
+    /// (ungrouped habits come last), and rows of up to `GRID_WIDTH` indices
+    /// into `habits`. With no groups at all there is one section and no
+    /// header, and a filter keeps only its group's section.
+    pub fn sections(&self) -> Vec<(Option<String>, Vec<Vec<usize>>)> {
+        let groups = self.groups();
+        let mut keys: Vec<Option<&str>> = groups.iter().copied().map(Some).collect();
+        keys.push(None);
+
+        keys.into_iter()
+            .filter(|key| self.filter.is_none() || *key == self.filter.as_deref())
+            .filter_map(|key| {
+                let members: Vec<usize> = (0..self.habits.len())
+                    .filter(|&i| self.habits[i].group() == key)
+                    .collect();
+                if members.is_empty() {
+                    return None;
+                }
+                let header = match key {
+                    Some(group) => Some(group.to_owned()),
+                    None if !groups.is_empty() => Some("ungrouped".to_owned()),
+                    None => None,
+                };
+                Some((
+                    header,
+                    members.chunks(GRID_WIDTH).map(<[_]>::to_vec).collect(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Every row of the grid as laid out on screen, across sections.
+    fn rows(&self) -> Vec<Vec<usize>> {
+        self.sections()
+            .into_iter()
+            .flat_map(|(_, rows)| rows)
+            .collect()
+    }
+
+    /// Height of the habit grid, group headers included.
+    fn grid_height(&self) -> usize {
+        self.sections()
+            .iter()
+            .map(|(header, rows)| usize::from(header.is_some()) + rows.len() * VIEW_HEIGHT)
+            .sum()
+    }
+
+    /// Show only the habits in `group`, or every habit for `None`. Focus moves
+    /// to the first shown habit if the focused one is hidden.
+    pub fn set_filter(&mut self, group: Option<String>) -> Result<(), String> {
+        if let Some(group) = &group
+            && !self.groups().contains(&group.as_str())
+        {
+            return Err(format!("Group `{group}` does not exist"));
+        }
+        self.filter = group;
+        self.focus_visible();
+        Ok(())
+    }
+
+    /// Step the filter through every group, then back to showing all habits.
+    pub fn cycle_filter(&mut self) {
+        let groups = self.groups();
+        let next = match &self.filter {
+            None => groups.first(),
+            Some(current) => groups
+                .iter()
+                .position(|g| g == current)
+                .and_then(|i| groups.get(i + 1)),
+        }
+        .map(|g| g.to_string());
+        self.filter = next;
+        self.focus_visible();
+    }
+
+    /// Keep the focus on a habit that is on screen, dropping a filter whose
+    /// group has no habits left.
+    pub(crate) fn focus_visible(&mut self) {
+        if let Some(group) = &self.filter
+            && !self.groups().contains(&group.as_str())
+        {
+            self.filter = None;
+        }
+        let rows = self.rows();
+        if !rows.iter().flatten().any(|&i| i == self.focus) {
+            self.focus = rows.first().and_then(|r| r.first()).copied().unwrap_or(0);
         }
     }
 
@@ -90,34 +409,44 @@ impl App {
         }
     }
 
+    /// Move focus across the grid as it is drawn, so with groups it follows
+    /// the sections rather than the order habits were added in.
     pub fn set_focus(&mut self, d: Absolute) {
-        if self.habits.is_empty() {
+        let rows = self.rows();
+        let shown: Vec<usize> = rows.iter().flatten().copied().collect();
+        let (Some(&first), Some(&last)) = (shown.first(), shown.last()) else {
             return;
-        }
+        };
+        let Some(pos) = shown.iter().position(|&i| i == self.focus) else {
+            self.focus = first;
+            return;
+        };
+        let (row, col) = rows
+            .iter()
+            .enumerate()
+            .find_map(|(r, cells)| cells.iter().position(|&i| i == self.focus).map(|c| (r, c)))
+            .unwrap_or_default();
+        let column_in = |r: usize| rows[r][col.min(rows[r].len() - 1)];
         match d {
             Absolute::Right => {
-                if self.focus != self.habits.len() - 1 {
-                    self.focus += 1;
+                if let Some(&next) = shown.get(pos + 1) {
+                    self.focus = next;
                 }
             }
             Absolute::Left => {
-                if self.focus != 0 {
-                    self.focus -= 1;
+                if pos > 0 {
+                    self.focus = shown[pos - 1];
                 }
             }
             Absolute::Down => {
-                if self.focus + GRID_WIDTH < self.habits.len() - 1 {
-                    self.focus += GRID_WIDTH;
+                self.focus = if row + 1 < rows.len() {
+                    column_in(row + 1)
                 } else {
-                    self.focus = self.habits.len() - 1;
-                }
+                    last
+                };
             }
             Absolute::Up => {
-                if self.focus as isize - GRID_WIDTH as isize >= 0 {
-                    self.focus -= GRID_WIDTH;
-                } else {
-                    self.focus = 0;
-                }
+                self.focus = if row > 0 { column_in(row - 1) } else { first };
             }
             Absolute::None => {}
         }
@@ -125,6 +454,25 @@ impl App {
 
     pub fn clear_message(&mut self) {
         self.message.clear();
+        self.message.set_kind(MessageKind::Info);
+    }
+
+    /// Run one command-mode line without the grid, as `dijo exec` and
+    /// `dijo -s` do. Returns what the command reported, if anything, or the
+    /// error it reported.
+    pub fn exec(&mut self, line: &str) -> Result<Option<String>, String> {
+        let line = line.strip_prefix(':').unwrap_or(line);
+        let command = line.parse::<Command>();
+        if let Ok(Command::Dashboard(_)) = command {
+            return Err("the dashboard can only be opened in the grid".into());
+        }
+        self.clear_message();
+        self.parse_command(command);
+        let report = self.message.contents().to_owned();
+        match self.message.kind() {
+            MessageKind::Error => Err(report),
+            _ => Ok(Some(report).filter(|r| !r.is_empty())),
+        }
     }
 
     /// Name and full description of the focused habit, for the `i` popup. The
@@ -145,11 +493,139 @@ impl App {
         Some((name, description))
     }
 
+    /// Set `name`'s entry on `date` to `input`, read in the habit's own unit:
+    /// decimals for number habits, `1h30` for durations, a state's label for
+    /// cycles. Goes through `TrackEvent::Set`, so `0` clears the day
+    /// (or is the score, for a rating whose scale starts at 0).
+    pub fn set_value(&mut self, name: &str, input: &str, date: NaiveDate) -> Result<(), String> {
+        let habit = self
+            .habits
+            .iter_mut()
+            .find(|h| h.name() == name)
+            .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+        let value = habit
+            .parse_value(input)
+            .ok_or_else(|| format!("`{input}` is not a value `{name}` can take"))?;
+        habit.modify(date, TrackEvent::Set(value));
+        Ok(())
+    }
+
+    /// Apply `change` to habit `name` on `date`, the way `dijo track` does:
+    /// `+N` and `-N` move the day up or down N steps, as the `n` and `p` keys
+    /// would, and `=VALUE` sets it like `:set`. A yes/no habit is marked done
+    /// by any `+N` and cleared by any `-N`, so tracking it twice keeps it done.
+    pub fn track_habit(&mut self, name: &str, change: &str, date: NaiveDate) -> Result<(), String> {
+        if date > Local::now().date_naive() {
+            return Err(format!("{date} is in the future"));
+        }
+        if let Some(value) = change.strip_prefix('=') {
+            return self.set_value(name, value, date);
+        }
+        let (up, times) = match change.split_at_checked(1) {
+            Some(("+", times)) => (true, times),
+            Some(("-", times)) => (false, times),
+            _ => return Err(format!("`{change}` is not +N, -N or =VALUE")),
+        };
+        let times = (times.parse::<u32>().ok())
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("`{change}` is not +N, -N or =VALUE"))?;
+        let habit = self
+            .habits
+            .iter_mut()
+            .find(|h| h.name() == name)
+            .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+        let current = (habit.entry_value(date)).and_then(|v| habit.parse_value(&v));
+        if current.is_none() && !up {
+            return Ok(());
+        }
+        let event = if up {
+            TrackEvent::Increment
+        } else {
+            TrackEvent::Decrement
+        };
+        match habit.kind() {
+            GoalKind::Bit => habit.modify(date, TrackEvent::Set(u32::from(up))),
+            // states wrap around, so only the last lap of steps matters
+            GoalKind::Cycle(states) => {
+                let len = states.len() as u32;
+                let times = match (up, current) {
+                    (false, _) => times.min(len + 1),
+                    (true, None) => 1 + (times - 1) % len.max(1),
+                    (true, Some(_)) => times % len.max(1),
+                };
+                (0..times).for_each(|_| habit.modify(date, event));
+            }
+            // past either end of the scale a step changes nothing
+            GoalKind::Rating(min, max) => {
+                let times = times.min(max.saturating_sub(min).saturating_add(2));
+                (0..times).for_each(|_| habit.modify(date, event));
+            }
+            _ => {
+                let by = (habit.step().checked_mul(times))
+                    .ok_or_else(|| format!("`{change}` is too large for `{name}`"))?;
+                let current = current.unwrap_or(0);
+                let value = if up {
+                    (current.checked_add(by))
+                        .ok_or_else(|| format!("`{change}` is too large for `{name}`"))?
+                } else {
+                    current.saturating_sub(by)
+                };
+                habit.modify(date, TrackEvent::Set(value));
+            }
+        }
+        Ok(())
+    }
+
+    /// The focused habit's name and the cursor date, which the `=` prompt
+    /// sets a value for.
+    pub fn focused_entry(&self) -> Option<(String, NaiveDate)> {
+        let habit = self.habits.get(self.focus)?;
+        Some((habit.name().to_owned(), self.cursor.0))
+    }
+
+    /// Progress on `date` of the given habits, as (completed, total): each
+    /// habit due that day counts once, whatever its unit, and is completed
+    /// once nothing remains of it.
+    pub(crate) fn progress<'a>(
+        &self,
+        indices: impl Iterator<Item = &'a usize>,
+        date: NaiveDate,
+    ) -> (u32, u32) {
+        // habits not scheduled for the day have nothing to do
+        indices
+            .map(|&i| &self.habits[i])
+            .filter(|h| h.is_due(date))
+            .fold((0, 0), |(done, due), h| {
+                (done + u32::from(h.remaining(date) == 0), due + 1)
+            })
+    }
+
+    /// Progress on `date` of each group on screen, as (group, completed,
+    /// total). Empty when no habit has a group.
+    pub fn group_progress(&self, date: NaiveDate) -> Vec<(String, u32, u32)> {
+        self.sections()
+            .into_iter()
+            .filter_map(|(header, rows)| {
+                let (completed, total) = self.progress(rows.iter().flatten(), date);
+                header.map(|name| (name, completed, total))
+            })
+            .collect()
+    }
+
     pub fn status(&self) -> StatusLine {
         let today = chrono::Local::now().naive_local().date();
-        let remaining = self.habits.iter().map(|h| h.remaining(today)).sum::<u32>();
-        let total = self.habits.iter().map(|h| h.goal()).sum::<u32>();
-        let completed = total - remaining;
+        let (completed, total) = self.progress(self.rows().iter().flatten(), today);
+        let remaining = total - completed;
+        let groups = self.group_progress(today);
+        let groups = if groups.is_empty() {
+            String::new()
+        } else {
+            let groups: Vec<String> = groups
+                .iter()
+                .map(|(name, completed, total)| format!("{name} {completed}/{total}"))
+                .collect();
+            format!(" [{}]", groups.join(", "))
+        };
 
         let timestamp = if self.cursor.0 == today {
             format!("{}", Local::now().naive_local().date().format("%d/%b/%y"),)
@@ -161,9 +637,10 @@ impl App {
 
         StatusLine(
             format!(
-                "Today: {} completed, {} remaining --{}--",
+                "Today: {} completed, {} remaining{} --{}--",
                 completed,
                 remaining,
+                groups,
                 self.get_mode()
             ),
             timestamp,
@@ -175,7 +652,29 @@ impl App {
     /// the habit name (for the dialog title) and the rendered body, or `None`
     /// when there are no habits.
     pub fn focused_dashboard(&self) -> Option<(String, StyledString)> {
-        let habit = self.habits.get(self.focus)?;
+        self.habits
+            .get(self.focus)
+            .map(|habit| Self::dashboard(habit.as_ref()))
+    }
+
+    /// The dashboard of habit `name`, retired ones included, so a retired
+    /// habit's history stays browsable. Reports an unknown habit on the
+    /// message line.
+    pub fn named_dashboard(&mut self, name: &str) -> Option<(String, StyledString)> {
+        if let Some(habit) = self.habits.iter().find(|h| h.name() == name) {
+            return Some(Self::dashboard(habit.as_ref()));
+        }
+        if let Some(habit) = self.retired.iter().find(|h| h.name() == name) {
+            let (name, body) = Self::dashboard(habit.as_ref());
+            return Some((format!("{name} (retired)"), body));
+        }
+        self.message.set_kind(MessageKind::Error);
+        self.message
+            .set_message(format!("Habit `{name}` does not exist"));
+        None
+    }
+
+    fn dashboard(habit: &dyn HabitWrapper) -> (String, StyledString) {
         let today = Local::now().date_naive();
 
         let reached_style = Style::from(CONFIGURATION.reached_color());
@@ -183,50 +682,100 @@ impl App {
         let inactive_style = Style::from(CONFIGURATION.inactive_color());
 
         let goal = habit.goal();
-        let dates_set: HashSet<NaiveDate> = habit.get_dates().into_iter().collect();
         let archived = &habit.inner_data_ref().archived_reached;
 
-        // A day counts as reached if it has an entry that meets the goal (i.e.
-        // nothing remaining), or it was reached in a now-archived month.
-        let is_reached = |d: NaiveDate| -> bool {
-            (dates_set.contains(&d) && habit.remaining(d) == 0) || archived.contains(&d)
-        };
-
-        // ---- all-time stats ----
-        let reached_dates: Vec<NaiveDate> = dates_set
-            .iter()
-            .copied()
-            .filter(|&d| habit.remaining(d) == 0)
+        // A day counts as reached if the habit met its goal on it, or it was
+        // reached in a now-archived month.
+        let reached_set: HashSet<NaiveDate> = habit
+            .reached_dates()
+            .into_iter()
             .chain(archived.iter().copied())
             .collect();
-        let s = habit_stats(&reached_dates, today, habit.period());
-        let unit = if habit.period() == GoalPeriod::Weekly {
-            "weeks"
-        } else {
-            "days"
+        let is_reached = |d: NaiveDate| -> bool { reached_set.contains(&d) };
+
+        // ---- all-time stats ----
+        let s = habit.stats(today);
+        let unit = match habit.period() {
+            GoalPeriod::Daily => "days",
+            GoalPeriod::Weekly => "weeks",
+            GoalPeriod::Monthly => "months",
         };
 
         let mut out = StyledString::new();
         if !habit.description().is_empty() {
             out.append_styled(format!("{}\n\n", habit.description()), inactive_style);
         }
-        out.append_plain(format!("goal: {goal} per {}\n\n", habit.period()));
-        out.append_plain(format!(
-            "  current streak   {:>4} {unit}\n",
-            s.current_streak
-        ));
-        out.append_plain(format!(
-            "  longest streak   {:>4} {unit}\n",
-            s.longest_streak
-        ));
-        out.append_plain(format!(
-            "  completed        {:>4} time{}\n",
-            s.total,
-            if s.total == 1 { "" } else { "s" }
-        ));
-        out.append_plain(format!("  completion rate  {:>4} %\n\n", s.completion_rate));
+        match habit.kind() {
+            GoalKind::Addiction(limit) => {
+                out.append_plain(format!("limit: at most {limit} per {}\n\n", habit.period()))
+            }
+            GoalKind::Duration(minutes) => out.append_plain(format!(
+                "goal: {} per {}\n\n",
+                Minutes(minutes),
+                habit.period()
+            )),
+            GoalKind::Rating(min, max) => {
+                out.append_plain(format!("scale: {min}–{max}, once a day\n\n"))
+            }
+            GoalKind::Cycle(states) => {
+                let states: Vec<String> = states
+                    .into_iter()
+                    .map(|(label, success)| if success { label + "*" } else { label })
+                    .collect();
+                out.append_plain(format!("states: {}\n\n", states.join(", ")));
+            }
+            _ => out.append_plain(format!("goal: {goal} per {}\n\n", habit.period())),
+        }
+        if !habit.schedule().is_every_day() {
+            out.append_plain(format!("on: {}\n", habit.schedule()));
+            match habit.days_until_due(today) {
+                Some(days) => out.append_plain(format!("{}\n\n", utils::due_label(days))),
+                None => out.append_plain("\n"),
+            }
+        }
+        if habit.range() != ActiveRange::default() {
+            out.append_plain(format!("tracked: {}\n\n", habit.range()));
+        }
+        if !habit.pauses().is_empty() {
+            // the most recent few are the ones worth a glance
+            const MAX_PAUSES: usize = 3;
+            let mut pauses = habit.pauses().to_vec();
+            pauses.sort_unstable_by_key(|p| std::cmp::Reverse(p.from));
+            let shown: Vec<String> = pauses
+                .iter()
+                .take(MAX_PAUSES)
+                .map(|p| p.to_string())
+                .collect();
+            out.append_plain(format!("paused: {}\n\n", shown.join(", ")));
+        }
+        if let Some(r) = habit.rating_summary() {
+            append_rating_summary(&mut out, &r, reached_style, inactive_style);
+        } else {
+            out.append_plain(format!(
+                "  current streak   {:>4} {unit}\n",
+                s.current_streak
+            ));
+            out.append_plain(format!(
+                "  longest streak   {:>4} {unit}\n",
+                s.longest_streak
+            ));
+            out.append_plain(format!(
+                "  completed        {:>4} time{}\n",
+                s.total,
+                if s.total == 1 { "" } else { "s" }
+            ));
+            out.append_plain(format!("  completion rate  {:>4} %\n", s.completion_rate));
+            if let (Some(left), Some(f)) = (s.freezes_left, habit.freezes()) {
+                out.append_plain(format!(
+                    "  freezes left     {left:>4} of {} this {}\n",
+                    f.count, f.per
+                ));
+            }
+            out.append_plain("\n");
+        }
 
         // ---- trailing-year heatmap ----
+        let paused_glyph = CONFIGURATION.look.paused_chr.to_string();
         const WEEKS: u64 = 53;
         const GUTTER: usize = 4; // width of the weekday-label column
         let weekday_off = today.weekday().num_days_from_monday() as u64;
@@ -274,7 +823,11 @@ impl App {
                 }
                 let (glyph, style) = if is_reached(date) {
                     ("█", reached_style)
-                } else if goal > 0 && habit.remaining(date) < goal {
+                } else if habit.is_paused(date) {
+                    (paused_glyph.as_str(), inactive_style)
+                } else if !habit.is_due(date) {
+                    (" ", inactive_style) // not on the schedule
+                } else if habit.goal_at(date) > 0 && habit.remaining(date) < habit.goal_at(date) {
                     ("▒", todo_style) // some progress, goal not met
                 } else {
                     ("░", inactive_style) // missed or no data
@@ -292,59 +845,113 @@ impl App {
         out.append_plain(" partial   ");
         out.append_styled("░", inactive_style);
         out.append_plain(" missed");
+        if !habit.pauses().is_empty() {
+            out.append_plain("   ");
+            out.append_styled(&paused_glyph, inactive_style);
+            out.append_plain(" paused");
+        }
+
+        // ---- notes, newest first ----
+        const MAX_NOTES: usize = 10;
+        let mut notes: Vec<(&NaiveDate, &String)> = habit.notes().iter().collect();
+        notes.sort_unstable_by(|a, b| b.0.cmp(a.0));
+        if !notes.is_empty() {
+            out.append_plain("\n\nnotes:\n");
+            for (date, note) in notes.iter().take(MAX_NOTES) {
+                out.append_styled(format!("  {date}  "), inactive_style);
+                out.append_plain(format!("{note}\n"));
+            }
+            if notes.len() > MAX_NOTES {
+                out.append_styled(
+                    format!("  ... {} older\n", notes.len() - MAX_NOTES),
+                    inactive_style,
+                );
+            }
+        }
 
-        Some((habit.name().to_owned(), out))
+        (habit.name().to_owned(), out)
     }
 
     pub fn max_size(&self) -> Vec2 {
         let width = GRID_WIDTH * VIEW_WIDTH;
-        let height = {
-            if !self.habits.is_empty() {
-                (VIEW_HEIGHT as f64 * (self.habits.len() as f64 / GRID_WIDTH as f64).ceil())
-                    as usize
-            } else {
-                0
-            }
-        };
-        Vec2::new(width, height + 2)
+        Vec2::new(width, self.grid_height() + 2)
     }
 
     pub fn load_state() -> Result<Self, String> {
         let regular_f = utils::habit_file()?;
-        let read_from_file = |file: PathBuf| -> Result<Vec<Box<dyn HabitWrapper>>, String> {
+        let read_from_file = |file: PathBuf| -> Result<App, String> {
             match File::open(file) {
                 Ok(ref mut f) => {
                     let mut j = String::new();
                     f.read_to_string(&mut j)
                         .map_err(|e| format!("Failed to read habit file: `{e}`"))?;
-                    serde_json::from_str(&j)
+                    App::from_habit_file(&j)
                         .map_err(|e| format!("Failed to parse habit file: `{e}`"))
                 }
                 // No file yet: a fresh start, not an error.
-                Err(_) => Ok(Vec::new()),
+                Err(_) => Ok(App::new()),
             }
         };
 
-        let mut regular = read_from_file(regular_f)?;
+        let mut app = read_from_file(regular_f)?;
 
         let archived = utils::load_archived_reached_goals();
-        for habit in regular.iter_mut() {
+        for habit in app.habits.iter_mut().chain(app.retired.iter_mut()) {
             if let Some(dates) = archived.get(habit.name()) {
                 habit.inner_data_mut_ref().archived_reached = dates.clone();
             }
+            record_range_start(habit.as_mut());
+        }
+        // streaks count archived months, so sort only once they are in
+        if let Some(key) = app.sort {
+            app.sort_habits(key, true);
         }
 
+        Ok(app)
+    }
+
+    /// Read the habit file: a plain array of habits, or, once a sort order
+    /// is remembered or a habit retired, an object holding them.
+    fn from_habit_file(j: &str) -> serde_json::Result<App> {
+        let value: serde_json::Value = serde_json::from_str(j)?;
+        let (habits, retired, sort) = match value {
+            serde_json::Value::Object(mut o) => (
+                o.remove("habits").unwrap_or_default(),
+                o.remove("retired")
+                    .unwrap_or_else(|| serde_json::Value::Array(Vec::new())),
+                o.remove("sort").map(serde_json::from_value).transpose()?,
+            ),
+            habits => (habits, serde_json::Value::Array(Vec::new()), None),
+        };
         Ok(App {
-            habits: regular,
-            ..Default::default()
+            habits: serde_json::from_value(habits)?,
+            retired: serde_json::from_value(retired)?,
+            sort,
+            ..App::new()
         })
     }
 
+    /// The habit file's contents; a plain array unless a sort order is
+    /// remembered or a habit retired, so older versions can still read it.
+    fn to_habit_file(&self) -> serde_json::Value {
+        let habits: Vec<_> = self.habits.iter().collect();
+        if self.sort.is_none() && self.retired.is_empty() {
+            return serde_json::json!(habits);
+        }
+        let mut file = serde_json::json!({ "habits": habits });
+        if let Some(sort) = self.sort {
+            file["sort"] = serde_json::json!(sort);
+        }
+        if !self.retired.is_empty() {
+            file["retired"] = serde_json::json!(self.retired);
+        }
+        file
+    }
+
     pub fn save_state(&self) -> Result<(), String> {
-        let regular: Vec<_> = self.habits.iter().collect();
         let file = utils::habit_file()?;
 
-        let mut o = serde_json::json!(regular);
+        let mut o = self.to_habit_file();
         o.sort_all_objects();
         let j = serde_json::to_string_pretty(&o)
             .map_err(|e| format!("could not serialize habits: {e}"))?;
@@ -372,6 +979,18 @@ impl App {
     }
 
     pub fn archive_habits(&mut self) {
+        match utils::archive_dir() {
+            Ok(archive_path) => self.archive_habits_in(&archive_path),
+            Err(e) => {
+                self.message.set_kind(MessageKind::Error);
+                self.message.set_message(e);
+            }
+        }
+    }
+
+    /// `archive_habits` into the archive files in `archive_path`. The months
+    /// moved out can't be undone back in, so the undo history is forgotten.
+    fn archive_habits_in(&mut self, archive_path: &Path) {
         let today = Local::now().date_naive();
         let current_month = today.month();
         let current_year = today.year();
@@ -382,8 +1001,10 @@ impl App {
 
         // Process each habit: check dates in Rust first, then serialize
         for habit in self.habits.iter() {
-            // Get all dates from the habit using the trait method
-            let dates = habit.get_dates();
+            // Get all dates from the habit using the trait method; a note on
+            // an otherwise empty day still belongs to its month
+            let mut dates = habit.get_dates();
+            dates.extend(habit.notes().keys().copied());
 
             // Check which months this habit's stats belong to
             let mut months_present: HashMap<(u32, i32), Vec<NaiveDate>> = HashMap::new();
@@ -414,6 +1035,7 @@ impl App {
                         }
                     });
                 }
+                retain_notes_in_month(&mut habit_json, month, year);
 
                 if month == current_month && year == current_year {
                     current_month_habits.push(habit_json);
@@ -433,20 +1055,13 @@ impl App {
                 if let Some(stats) = habit_json.get_mut("stats").and_then(|s| s.as_object_mut()) {
                     stats.clear();
                 }
+                retain_notes_in_month(&mut habit_json, current_month, current_year);
 
                 current_month_habits.push(habit_json);
             }
         }
 
         // Write archived habits to files
-        let archive_path = match utils::archive_dir() {
-            Ok(p) => p,
-            Err(e) => {
-                self.message.set_kind(MessageKind::Error);
-                self.message.set_message(e);
-                return;
-            }
-        };
         let mut archived_count = 0;
 
         for ((month, year), habits) in habits_by_month.iter() {
@@ -456,8 +1071,7 @@ impl App {
             let month_name = date.format("%b").to_string().to_lowercase();
 
             let filename = format!("{}_{}.json", month_name, year);
-            let mut file_path = archive_path.clone();
-            file_path.push(filename);
+            let file_path = archive_path.join(filename);
 
             let mut o = serde_json::json!(habits);
             o.sort_all_objects();
@@ -484,6 +1098,7 @@ impl App {
         // Update habits with current month data only
         self.habits = serde_json::from_value(serde_json::Value::Array(current_month_habits))
             .unwrap_or_else(|_| Vec::new());
+        self.forget_history();
 
         if archived_count > 0 {
             self.message
@@ -494,29 +1109,57 @@ impl App {
     }
 
     pub fn parse_command(&mut self, result: Result<Command, CommandLineError>) {
+        // an edit to a single habit is undone by restoring how it was stored
+        let edit = result
+            .as_ref()
+            .ok()
+            .and_then(edited_habit)
+            .map(|(what, name)| {
+                let before = self.stored(&name);
+                (what, name, before)
+            });
         match result {
             Ok(c) => match c {
-                Command::Add(name, goal, period) => {
-                    if self.habits.iter().any(|x| x.name() == name) {
+                Command::Add(name, goal, period, group, step) => {
+                    if self.name_taken(&name) {
                         self.message.set_kind(MessageKind::Error);
                         self.message
                             .set_message(format!("Habit `{name}` already exist"));
                         return;
                     }
-                    match goal {
-                        Some(GoalKind::Bit) => {
-                            self.add_habit(Box::new(Bit::new(name)));
-                        }
+                    let mut habit: Box<dyn HabitWrapper> = match goal {
+                        Some(GoalKind::Bit) => Box::new(Bit::new(name)),
                         Some(GoalKind::Count(v)) => {
-                            self.add_habit(Box::new(Count::new(name, v).with_period(period)));
+                            Box::new(Count::new(name, v).with_period(period))
                         }
                         Some(GoalKind::Float(v, p)) => {
-                            self.add_habit(Box::new(Float::new(name, v, p).with_period(period)));
+                            Box::new(Float::new(name, v, p).with_period(period))
                         }
-                        _ => {
-                            self.add_habit(Box::new(Count::new(name, 0)));
+                        Some(GoalKind::Duration(m)) => {
+                            Box::new(Duration::new(name, m).with_period(period))
                         }
+                        Some(GoalKind::Rating(min, max)) => Box::new(Rating::new(name, min, max)),
+                        Some(GoalKind::Cycle(states)) => Box::new(Cycle::new(name, states)),
+                        Some(GoalKind::Addiction(v)) => Box::new(Addiction::new(name, v)),
+                        None => Box::new(Count::new(name, 0)),
+                    };
+                    // days before a habit existed are not missed
+                    habit.set_range(ActiveRange::starting(Local::now().date_naive()));
+                    habit.set_group(group);
+                    if let Some(step) = step
+                        && let Err(e) = read_step(habit.as_mut(), &step)
+                    {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                        return;
                     }
+                    let name = habit.name().to_owned();
+                    self.add_habit(habit);
+                    let change = Change::Inserted {
+                        name: name.clone(),
+                        retired: false,
+                    };
+                    self.record(format!("add `{name}`"), change);
                 }
                 Command::Describe(name, description) => {
                     if let Some(habit) = self.habits.iter_mut().find(|h| h.name() == name) {
@@ -527,26 +1170,230 @@ impl App {
                             .set_message(format!("Habit `{name}` does not exist"));
                     }
                 }
+                Command::Set(name, value, date) => {
+                    let date = date.unwrap_or(self.cursor.0);
+                    if let Err(e) = self.set_value(&name, &value, date) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Step(name, step) => {
+                    let result = match self.habits.iter_mut().find(|h| h.name() == name) {
+                        Some(habit) => read_step(habit.as_mut(), &step),
+                        None => Err(format!("Habit `{name}` does not exist")),
+                    };
+                    if let Err(e) = result {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Note(name, note) => {
+                    let date = self.cursor.0;
+                    if let Some(habit) = self.habits.iter_mut().find(|h| h.name() == name) {
+                        habit.set_note(date, note);
+                    } else {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message
+                            .set_message(format!("Habit `{name}` does not exist"));
+                    }
+                }
+                Command::Schedule(name, schedule) => {
+                    if let Some(habit) = self.habits.iter_mut().find(|h| h.name() == name) {
+                        habit.set_schedule(schedule);
+                    } else {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message
+                            .set_message(format!("Habit `{name}` does not exist"));
+                    }
+                }
+                Command::Range(name, range) => {
+                    if let Some(habit) = self.habits.iter_mut().find(|h| h.name() == name) {
+                        habit.set_range(range);
+                    } else {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message
+                            .set_message(format!("Habit `{name}` does not exist"));
+                    }
+                }
+                Command::Freeze(name, freezes) => {
+                    match self.habits.iter_mut().find(|h| h.name() == name) {
+                        // the allowance has to renew less often than the goal
+                        Some(habit) if freezes.is_some_and(|f| f.per <= habit.period()) => {
+                            self.message.set_kind(MessageKind::Error);
+                            self.message.set_message(format!(
+                                "Freezes for `{name}` must renew over a longer period than its goal"
+                            ));
+                        }
+                        Some(habit) => habit.set_freezes(freezes),
+                        None => {
+                            self.message.set_kind(MessageKind::Error);
+                            self.message
+                                .set_message(format!("Habit `{name}` does not exist"));
+                        }
+                    }
+                }
+                Command::Goal(name, kind, period) => {
+                    let today = Local::now().date_naive();
+                    let result = match self.habits.iter_mut().find(|h| h.name() == name) {
+                        Some(habit) if habit.freezes().is_some_and(|f| f.per <= period) => {
+                            Err(format!(
+                                "Freezes for `{name}` must renew over a longer period than its goal"
+                            ))
+                        }
+                        Some(habit) => habit.set_goal(kind, period, today),
+                        None => Err(format!("Habit `{name}` does not exist")),
+                    };
+                    if let Err(msg) = result {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(msg);
+                    }
+                }
+                Command::Rename(old, new) => {
+                    if let Err(e) = self.rename_habit(&old, &new) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Convert(name, kind, period) => {
+                    let Some(index) = self.habits.iter().position(|h| h.name() == name) else {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message
+                            .set_message(format!("Habit `{name}` does not exist"));
+                        return;
+                    };
+                    if self.habits[index]
+                        .freezes()
+                        .is_some_and(|f| f.per <= period)
+                    {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(format!(
+                            "Freezes for `{name}` must renew over a longer period than its goal"
+                        ));
+                        return;
+                    }
+                    // a goal of `1` reads as yes/no, but a count keeps counting
+                    let kind = match (kind, self.habits[index].kind()) {
+                        (GoalKind::Bit, GoalKind::Count(_)) => GoalKind::Count(1),
+                        (kind, _) => kind,
+                    };
+                    match convert(self.habits[index].as_ref(), &kind, period) {
+                        Ok((mut converted, lossy)) => {
+                            let old = &mut self.habits[index];
+                            *converted.inner_data_mut_ref() =
+                                std::mem::take(old.inner_data_mut_ref());
+                            *old = converted;
+                            if lossy > 0 {
+                                self.message.set_kind(MessageKind::Info);
+                                self.message.set_message(format!(
+                                    "Converted `{name}`; {lossy} day(s) were rounded to fit"
+                                ));
+                            }
+                        }
+                        Err(e) => {
+                            self.message.set_kind(MessageKind::Error);
+                            self.message.set_message(e);
+                        }
+                    }
+                }
+                Command::Pause(target, pause) => {
+                    self.pause_habits(&target, &[pause]);
+                }
+                Command::Unpause(target, span) => {
+                    self.unpause_habits(&target, span);
+                }
+                Command::ImportPauses(target, path) => {
+                    match std::fs::read_to_string(utils::expand_home(&path)) {
+                        Ok(text) => {
+                            let pauses = ics::parse_pauses(&text);
+                            if self.pause_habits(&target, &pauses) {
+                                self.message.set_kind(MessageKind::Info);
+                                self.message.set_message(format!(
+                                    "Imported {} pause(s) from `{path}`",
+                                    pauses.len()
+                                ));
+                            }
+                        }
+                        Err(e) => {
+                            self.message.set_kind(MessageKind::Error);
+                            self.message
+                                .set_message(format!("Could not read `{path}`: {e}"));
+                        }
+                    }
+                }
                 Command::Delete(name) => {
                     self.delete_by_name(&name);
                     self.focus = 0;
+                    self.focus_visible();
+                }
+                Command::Group(name, group) => {
+                    if let Some(habit) = self.habits.iter_mut().find(|h| h.name() == name) {
+                        habit.set_group(group);
+                        self.focus_visible();
+                    } else {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message
+                            .set_message(format!("Habit `{name}` does not exist"));
+                    }
+                }
+                Command::Move(name, position) => {
+                    if let Err(e) = self.move_habit(&name, position) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Sort(Some(key), keep) => self.sort_habits(key, keep),
+                Command::Sort(None, _) => self.sort = None,
+                Command::Retire(name) => {
+                    if let Err(e) = self.retire_habit(&name) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Unretire(name) => {
+                    if let Err(e) = self.unretire_habit(&name) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
+                }
+                Command::Filter(group) => {
+                    if let Err(e) = self.set_filter(group) {
+                        self.message.set_kind(MessageKind::Error);
+                        self.message.set_message(e);
+                    }
                 }
                 Command::Help(input) => {
                     if let Some(topic) = input.as_ref().map(String::as_ref) {
                         self.message.set_message(
                             match topic {
-                                "a"     | "add" => "add <habit-name> [goal[/week]]   e.g. `add gym 3/week`  (alias: a)",
+                                "a"     | "add" => "add <habit-name> [goal[/week|/month]|<limit|cycle:a,b*|rating:1-5] [--group <group>] [step:<n>]   e.g. `add gym 3/week`, `add pushups 100 step:10`, `add runs 4/month`, `add read 30m`, `add coffee <2`  (alias: a)",
                                 "describe" | "desc" => "describe <habit-name> <text...>     (alias: desc)",
+                                "note" => "note <habit-name> [text...]   note the cursor date; no text clears it",
+                                "step" => "step <habit-name> <step>   how much n/p add or take, e.g. `step pushups 10`, `step run 0.5`",
+                                "set" => "set <habit-name> <value> [date]   e.g. `set run 8.5`, `set read 1h30 yesterday`; 0 clears the day  (key: =)",
+                                "schedule" | "sched" => "schedule <habit-name> [days]   e.g. `schedule gym mon,wed,fri` or `schedule plants every:3`; no days means every day  (alias: sched)",
+                                "range" => "range <habit-name> <start> [end]   dates as YYYY-MM-DD or `today`; days outside are not tracked",
+                                "goal" => "goal <habit-name> <goal[/week|/month]|<limit>   e.g. `goal read 45m`; earlier days keep the old goal",
+                                "group" => "group <habit-name> [group]   file a habit under a group; no group ungroups it",
+                                "filter" => "filter [group]   show only one group's habits; no group shows all  (key: g cycles)",
+                                "move" => "move <habit-name> <position>   position counts from 1  (keys: < >)",
+                                "sort" => "sort name|remaining|streak|group|none [keep]   `keep` re-sorts every time dijo starts; `none` forgets it",
+                                "rename" => "rename <habit-name> <new-name>   also renames it in archived months",
+                                "convert" => "convert <habit-name> <goal[/week|/month]>   switch between yes/no, count and number habits, keeping entries",
+                                "freeze" => "freeze <habit-name> [N/week|N/month]   missed days the streak survives; none or 0 turns it off",
+                                "pause" => "pause <habit-name|all> <from> <to>  or  pause <habit-name|all> <file.ics>   paused days are not missed and keep streaks",
+                                "unpause" => "unpause <habit-name|all> [<from> <to>]   without dates every pause is removed",
                                 "d"     | "delete" => "delete <habit-name>     (alias: d)",
                                 "mprev" | "month-prev" => "month-prev     (alias: mprev)",
                                 "mnext" | "month-next" => "month-next     (alias: mnext)",
                                 "archive" => "archive old months to separate files",
-                                "dashboard" | "dash" => "open the focused habit's dashboard     (alias: dash, key: d)",
+                                "dashboard" | "dash" => "dashboard [habit-name]   open the focused or named habit's dashboard, retired ones too     (alias: dash, key: d)",
+                                "retire" => "retire <habit-name>   take a finished habit off the grid, keeping its history",
+                                "unretire" => "unretire <habit-name>   put a retired habit back on the grid",
                                 "q"     | "quit" => "quit dijo",
                                 "w"     | "write" => "write current state to disk   (alias: w)",
                                 "h"|"?" | "help" => "help [<command>|commands|keys]     (aliases: h, ?)",
-                                "cmds"  | "commands" => "add, describe, delete, month-{prev,next}, archive, dashboard, help, quit",
-                                "keys" => "hjkl: move | HJKL: cursor | n/Enter: +1 | p/BS: -1 | v: cycle view (day/week/month/sparkline/year/stats/heatmap) | d: dashboard | i: description popup | []: month | Esc: reset",
+                                "cmds"  | "commands" => "add, describe, rename, group, filter, move, sort, retire, unretire, note, set, step, schedule, range, goal, convert, pause, unpause, freeze, delete, month-{prev,next}, archive, dashboard, help, quit",
+                                "keys" => "hjkl: move | HJKL: cursor | n/Enter: +step | p/BS: -step | =: set value | u/Ctrl-r: undo/redo | v: cycle view (day/week/month/sparkline/year/stats/heatmap) | d: dashboard | i: description popup | g: cycle group filter | < >: move habit | []: month | Esc: reset",
                                 "wq" =>   "write current state to disk and quit dijo",
                                 _ => "unknown command or help topic.",
                             }
@@ -572,7 +1419,7 @@ impl App {
                 }
                 // opening the dashboard needs access to the Cursive root, so it
                 // is handled in command::call_on_app, not here.
-                Command::Dashboard => {}
+                Command::Dashboard(_) => {}
                 Command::Blank => {}
             },
             Err(e) => {
@@ -580,9 +1427,46 @@ impl App {
                 self.message.set_kind(MessageKind::Error);
             }
         }
+        if let Some((what, name, before)) = edit {
+            self.record_edit(what, &name, before);
+        }
     }
 }
 
+/// Drop every note in a serialized habit that falls outside `month`/`year`.
+fn retain_notes_in_month(habit_json: &mut serde_json::Value, month: u32, year: i32) {
+    if let Some(notes) = habit_json.get_mut("notes").and_then(|n| n.as_object_mut()) {
+        notes.retain(|date_str, _| {
+            NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+                .is_ok_and(|date| date.month() == month && date.year() == year)
+        });
+    }
+}
+
+/// The rating part of the dashboard: average, a histogram of how often each
+/// value was given, and the average per weekday.
+fn append_rating_summary(out: &mut StyledString, r: &RatingStats, bar: Style, dim: Style) {
+    const BAR_WIDTH: u32 = 30;
+    let avg = |a: Option<f64>| a.map_or_else(|| "  - ".to_owned(), |a| format!("{a:>4.1}"));
+
+    out.append_plain(format!("  average          {}\n\n", avg(r.average)));
+
+    let most = r.distribution.iter().map(|&(_, n)| n).max().unwrap_or(0);
+    for &(value, n) in r.distribution.iter().rev() {
+        out.append_plain(format!("  {value:>3} "));
+        let len = (n * BAR_WIDTH).checked_div(most).unwrap_or(0);
+        out.append_styled("█".repeat(len as usize), bar);
+        out.append_styled(format!(" {n}\n"), dim);
+    }
+
+    out.append_plain("\n ");
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    for (day, a) in WEEKDAYS.iter().zip(r.weekday_averages) {
+        out.append_plain(format!(" {day}{}", avg(a)));
+    }
+    out.append_plain("\n\n");
+}
+
 fn month_abbr(month: u32) -> &'static str {
     const NAMES: [&str; 12] = [
         "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
@@ -590,10 +1474,38 @@ fn month_abbr(month: u32) -> &'static str {
     NAMES.get((month as usize).wrapping_sub(1)).unwrap_or(&"")
 }
 
+/// Set `habit`'s step to `input`, read in the habit's own unit like a value
+/// given to `:set`.
+fn read_step(habit: &mut dyn HabitWrapper, input: &str) -> Result<(), String> {
+    let step = habit
+        .parse_value(input)
+        .ok_or_else(|| format!("`{input}` is not a step `{}` can take", habit.name()))?;
+    habit.set_step(step)
+}
+
+/// Give a habit kept from before active ranges were recorded a start at its
+/// first entry, archived months included, so that `is_active` need not look
+/// it up for every day it is asked about.
+fn record_range_start(habit: &mut dyn HabitWrapper) {
+    let range = habit.range();
+    if range.start.is_some() {
+        return;
+    }
+    let first = (habit.get_dates().into_iter())
+        .chain(habit.inner_data_ref().archived_reached.iter().copied())
+        .min();
+    if first.is_some() {
+        habit.set_range(ActiveRange {
+            start: first,
+            ..range
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::habit::{Count, Habit};
+    use crate::habit::{Count, Habit, Schedule, TrackEvent};
 
     #[test]
     fn dashboard_is_none_without_habits() {
@@ -658,6 +1570,583 @@ mod tests {
         assert!(app.message.contents().contains("no description"));
     }
 
+    #[test]
+    fn note_lands_on_the_cursor_date_and_shows_in_dashboard() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("run", 1)));
+        app.parse_command("note run ran 5k in the rain".parse());
+
+        let today = Local::now().date_naive();
+        assert_eq!(
+            app.habits[0].notes().get(&today).unwrap(),
+            "ran 5k in the rain"
+        );
+        let (_, body) = app.focused_dashboard().expect("dashboard");
+        assert!(
+            body.source()
+                .contains(&format!("{today}  ran 5k in the rain"))
+        );
+
+        app.parse_command("note run".parse());
+        assert!(app.habits[0].notes().is_empty());
+    }
+
+    #[test]
+    fn set_reads_the_value_in_the_habits_unit() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Float::new("run", 50, 1)));
+        app.add_habit(Box::new(Duration::new("read", 30)));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        app.parse_command("set run 8.5 2024-01-03".parse());
+        app.parse_command("set read 1h30 2024-01-03".parse());
+        assert_eq!(app.habits[0].remaining(day), 0);
+        assert_eq!(app.habits[1].remaining(day), 0);
+
+        app.parse_command("set run 0 2024-01-03".parse());
+        assert_eq!(app.habits[0].remaining(day), 50, "0 clears the day");
+    }
+
+    #[test]
+    fn step_is_read_in_the_habits_unit() {
+        let mut app = App::new();
+        app.parse_command("add run 5.0 step:0.5".parse());
+        assert_eq!(app.habits[0].step(), 5);
+
+        app.parse_command("step run 1.25".parse());
+        assert!(app.message.contents().contains("not a step"));
+        app.parse_command("step run 1".parse());
+        assert_eq!(app.habits[0].step(), 10);
+
+        app.parse_command("add read 30m step:x".parse());
+        assert_eq!(app.habits.len(), 1, "a bad step does not add the habit");
+    }
+
+    #[test]
+    fn track_steps_or_sets_a_day() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("pushups", 100)));
+        app.parse_command("step pushups 10".parse());
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        app.track_habit("pushups", "+3", day).unwrap();
+        assert_eq!(app.habits[0].remaining(day), 70);
+        app.track_habit("pushups", "-1", day).unwrap();
+        assert_eq!(app.habits[0].remaining(day), 80);
+        app.track_habit("pushups", "=100", day).unwrap();
+        assert_eq!(app.habits[0].remaining(day), 0);
+    }
+
+    #[test]
+    fn tracking_a_yes_no_habit_twice_keeps_it_done() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Bit::new("floss")));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        app.track_habit("floss", "+1", day).unwrap();
+        app.track_habit("floss", "+1", day).unwrap();
+        assert!(app.habits[0].reached_dates().contains(&day));
+        app.track_habit("floss", "-2", day).unwrap();
+        assert!(app.habits[0].get_dates().is_empty());
+    }
+
+    #[test]
+    fn huge_steps_are_computed_at_once() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("pushups", 100)));
+        app.add_habit(Box::new(Cycle::new(
+            "mood",
+            vec![("bad".into(), false), ("good".into(), true)],
+        )));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+
+        app.track_habit("pushups", "+4000000000", day).unwrap();
+        assert_eq!(app.habits[0].remaining(day), 0);
+        assert!(app.track_habit("pushups", "+4000000000", day).is_err());
+        app.track_habit("pushups", "-4000000000", day).unwrap();
+        assert!(app.habits[0].get_dates().is_empty());
+
+        app.track_habit("mood", "+4000000000", day).unwrap();
+        assert!(app.habits[1].reached_dates().contains(&day));
+    }
+
+    #[test]
+    fn track_rejects_bad_changes_and_unknown_habits() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("pushups", 100)));
+        let day = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        for change in ["3", "+", "+0", "-x", "=ten"] {
+            assert!(app.track_habit("pushups", change, day).is_err(), "{change}");
+        }
+        assert!(app.track_habit("swim", "+1", day).is_err());
+        let tomorrow = Local::now().date_naive().succ_opt().unwrap();
+        assert!(app.track_habit("pushups", "+1", tomorrow).is_err());
+    }
+
+    #[test]
+    fn exec_reports_errors_and_what_commands_said() {
+        let mut app = App::new();
+        assert_eq!(app.exec(":add gym 3/week"), Ok(None));
+        assert_eq!(app.list_habits(), ["gym"]);
+        assert_eq!(
+            app.exec("add gym 2"),
+            Err("Habit `gym` already exist".into())
+        );
+        assert!(app.exec("frobnicate").is_err());
+        assert!(app.exec("delete swim").is_err());
+        assert!(app.exec("dashboard gym").is_err());
+        assert!(matches!(app.exec("help add"), Ok(Some(_))));
+        assert_eq!(
+            app.exec("delete gym"),
+            Ok(None),
+            "earlier errors are cleared"
+        );
+    }
+
+    #[test]
+    fn set_rejects_values_the_habit_cannot_take() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Float::new("run", 50, 1)));
+        app.parse_command("set run 8.55".parse());
+        assert!(app.message.contents().contains("`8.55` is not a value"));
+
+        app.parse_command("set swim 2".parse());
+        assert!(app.message.contents().contains("does not exist"));
+    }
+
+    #[test]
+    fn unscheduled_habits_drop_out_of_todays_status() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("read", 2)));
+        app.add_habit(Box::new(Count::new("gym", 1)));
+        let tomorrow = Local::now().date_naive().succ_opt().unwrap().weekday();
+        app.parse_command(Ok(Command::Schedule(
+            "gym".into(),
+            Schedule::new(vec![tomorrow]),
+        )));
+
+        assert!(app.status().0.contains("0 completed, 1 remaining"));
+        assert!(
+            app.habits[1]
+                .missed_dates()
+                .iter()
+                .all(|d| d.weekday() == tomorrow)
+        );
+    }
+
+    #[test]
+    fn habits_without_a_start_get_their_first_entry_as_one() {
+        let d = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let mut read: Box<dyn HabitWrapper> = Box::new(Count::new("read", 1));
+        read.modify(d(5), TrackEvent::Set(1));
+        read.inner_data_mut_ref().archived_reached.insert(d(3));
+        record_range_start(read.as_mut());
+        assert_eq!(read.range(), ActiveRange::starting(d(3)));
+
+        read.set_range(ActiveRange::starting(d(1)));
+        record_range_start(read.as_mut());
+        assert_eq!(read.range().start, Some(d(1)), "a recorded start is kept");
+    }
+
+    #[test]
+    fn added_habits_start_today_and_miss_nothing_before() {
+        let mut app = App::new();
+        app.parse_command("add read 2".parse());
+        let today = Local::now().date_naive();
+        assert_eq!(app.habits[0].range(), ActiveRange::starting(today));
+        assert!(app.habits[0].missed_dates().is_empty());
+        assert!(!app.habits[0].is_due(today.pred_opt().unwrap()));
+    }
+
+    #[test]
+    fn range_bounds_the_days_a_habit_is_due() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("read", 2)));
+        app.parse_command("range read 2024-10-01 2024-10-31".parse());
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert!(!app.habits[0].is_due(date(9, 30)));
+        assert!(app.habits[0].is_due(date(10, 1)));
+        assert!(app.habits[0].is_due(date(10, 31)));
+        assert!(!app.habits[0].is_due(date(11, 1)));
+
+        app.parse_command("range ghost today".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
+    #[test]
+    fn addictions_miss_only_the_days_over_their_limit() {
+        let today = Local::now().date_naive();
+        let first = today.with_day(1).unwrap();
+        let mut coffee = Addiction::new("coffee", 1);
+        Habit::set_range(&mut coffee, ActiveRange::starting(first));
+        if first < today {
+            Habit::insert_entry(&mut coffee, first, 3);
+        }
+        let coffee: Box<dyn HabitWrapper> = Box::new(coffee);
+        let missed = if first < today { vec![first] } else { vec![] };
+        assert_eq!(coffee.missed_dates(), missed, "clean days are not missed");
+    }
+
+    #[test]
+    fn pausing_all_habits_clears_their_missed_days() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("read", 2)));
+        app.add_habit(Box::new(Count::new("gym", 1)));
+        let today = Local::now().date_naive();
+        let first = today.with_day(1).unwrap();
+        app.parse_command(Ok(Command::Pause("all".into(), Pause::new(first, today))));
+
+        for habit in &app.habits {
+            assert!(habit.missed_dates().is_empty());
+            assert!(habit.is_paused(today));
+        }
+        app.parse_command(Ok(Command::Pause("ghost".into(), Pause::new(first, today))));
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
+    #[test]
+    fn unpausing_all_habits_brings_their_days_back() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("read", 2)));
+        app.add_habit(Box::new(Count::new("gym", 1)));
+        let day = |d| NaiveDate::from_ymd_opt(2024, 8, d).unwrap();
+        app.parse_command("pause all 2024-08-01 2024-08-14".parse());
+        app.parse_command("unpause gym 2024-08-10 2024-08-20".parse());
+        assert!(app.habits[1].is_paused(day(9)));
+        assert!(!app.habits[1].is_paused(day(10)));
+
+        app.parse_command("unpause all".parse());
+        assert!(app.habits.iter().all(|h| h.pauses().is_empty()));
+        app.parse_command("unpause ghost".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
+    #[test]
+    fn freezes_must_renew_slower_than_the_goal() {
+        let mut app = App::new();
+        app.add_habit(Box::new(
+            Count::new("gym", 3).with_period(GoalPeriod::Weekly),
+        ));
+        app.parse_command("freeze gym 1/week".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+        assert_eq!(app.habits[0].freezes(), None);
+
+        app.parse_command("freeze gym 1/month".parse());
+        assert_eq!(
+            app.habits[0].freezes().map(|f| (f.count, f.per)),
+            Some((1, GoalPeriod::Monthly))
+        );
+    }
+
+    #[test]
+    fn goal_change_keeps_earlier_days_on_the_old_goal() {
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let mut app = App::new();
+        let mut habit = Count::new("pushups", 20);
+        habit.insert_entry(yesterday, 20);
+        habit.insert_entry(today, 20);
+        app.add_habit(Box::new(habit));
+
+        app.parse_command("goal pushups 30".parse());
+        assert_eq!(app.habits[0].goal(), 30);
+        assert_eq!(app.habits[0].remaining(yesterday), 0);
+        assert_eq!(app.habits[0].remaining(today), 10);
+
+        app.parse_command("goal pushups 30m".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+        app.parse_command("goal ghost 3".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
+    #[test]
+    fn convert_keeps_entries_and_reports_rounding() {
+        let today = Local::now().date_naive();
+        let mut app = App::new();
+        let mut habit = Count::new("run", 3);
+        habit.insert_entry(today, 4);
+        app.add_habit(Box::new(habit));
+        app.parse_command(Ok(Command::Describe("run".into(), "km".into())));
+
+        app.parse_command("convert run 2.5".parse());
+        assert_eq!(app.habits[0].kind(), GoalKind::Float(25, 1));
+        assert_eq!(app.habits[0].description(), "km");
+        assert_eq!(app.habits[0].remaining(today), 0);
+
+        app.parse_command("convert run 1".parse());
+        assert_eq!(app.habits[0].kind(), GoalKind::Bit);
+        assert!(matches!(app.message.kind(), MessageKind::Info));
+        assert!(app.message.contents().contains("1 day(s)"));
+
+        app.add_habit(Box::new(Count::new("pushups", 3)));
+        app.parse_command("convert pushups 1".parse());
+        assert_eq!(app.habits[1].kind(), GoalKind::Count(1));
+    }
+
+    #[test]
+    fn rename_refuses_taken_and_unknown_names() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 3)));
+        app.add_habit(Box::new(Count::new("read", 1)));
+
+        assert!(app.rename_habit("gym", "read").is_err());
+        assert!(app.rename_habit("gym", "all").is_err());
+        assert!(app.rename_habit("ghost", "spirit").is_err());
+        assert_eq!(app.habits[0].name(), "gym");
+    }
+
+    #[test]
+    fn rename_rewrites_archived_months_before_the_habit() {
+        let archive = std::env::temp_dir().join(format!("dijo-rename-{}", std::process::id()));
+        std::fs::create_dir_all(&archive).unwrap();
+        let month = archive.join("1_2024.json");
+        std::fs::write(&month, r#"[{"type": "Count", "name": "gym", "goal": 3}]"#).unwrap();
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 3)));
+
+        app.rename_habit_in(&archive, "gym", "lifting").unwrap();
+        assert_eq!(app.habits[0].name(), "lifting");
+        let rewritten = std::fs::read_to_string(&month).unwrap();
+        assert!(rewritten.contains(r#""name": "lifting""#), "{rewritten}");
+
+        std::fs::remove_dir_all(&archive).unwrap();
+        assert!(app.rename_habit_in(&archive, "lifting", "gym").is_err());
+        assert_eq!(
+            app.habits[0].name(),
+            "lifting",
+            "a failed rename changes nothing"
+        );
+    }
+
+    #[test]
+    fn archiving_forgets_what_could_be_undone() {
+        let archive = std::env::temp_dir().join(format!("dijo-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&archive).unwrap();
+        let old_day = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 3)));
+        app.habits[0].modify(old_day, TrackEvent::Set(2));
+        app.parse_command("goal gym 5".parse());
+
+        app.archive_habits_in(&archive);
+        assert!(archive.join("jan_2024.json").exists());
+        assert!(app.habits[0].get_dates().is_empty());
+        app.undo();
+        assert_eq!(app.message.contents(), "Nothing to undo");
+        assert!(
+            app.habits[0].get_dates().is_empty(),
+            "archived days stay in the archive only"
+        );
+        std::fs::remove_dir_all(&archive).unwrap();
+    }
+
+    #[test]
+    fn rename_refuses_names_only_the_archive_has() {
+        let archive =
+            std::env::temp_dir().join(format!("dijo-rename-taken-{}", std::process::id()));
+        std::fs::create_dir_all(&archive).unwrap();
+        let month = archive.join("jan_2024.json");
+        let stored = r#"[{"type": "Count", "name": "gym", "goal": 3}, {"type": "Count", "name": "swim", "goal": 1}]"#;
+        std::fs::write(&month, stored).unwrap();
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 3)));
+
+        assert!(app.rename_habit_in(&archive, "gym", "swim").is_err());
+        assert_eq!(app.habits[0].name(), "gym");
+        assert_eq!(std::fs::read_to_string(&month).unwrap(), stored);
+        std::fs::remove_dir_all(&archive).unwrap();
+    }
+
+    fn grouped_app() -> App {
+        let mut app = App::new();
+        for (name, group) in [
+            ("gym", Some("health")),
+            ("read", Some("learning")),
+            ("floss", None),
+            ("run", Some("health")),
+        ] {
+            let mut habit = Count::new(name, 2);
+            Habit::set_group(&mut habit, group.map(String::from));
+            app.add_habit(Box::new(habit));
+        }
+        app
+    }
+
+    #[test]
+    fn sections_follow_groups_with_ungrouped_last() {
+        let app = grouped_app();
+        let sections = app.sections();
+        let headers: Vec<_> = sections.iter().map(|(h, _)| h.as_deref()).collect();
+        assert_eq!(
+            headers,
+            [Some("health"), Some("learning"), Some("ungrouped")]
+        );
+        assert_eq!(sections[0].1, vec![vec![0, 3]]);
+
+        let mut plain = App::new();
+        plain.add_habit(Box::new(Count::new("gym", 2)));
+        assert_eq!(plain.sections(), vec![(None, vec![vec![0]])]);
+    }
+
+    #[test]
+    fn focus_moves_through_the_grid_as_drawn() {
+        let mut app = grouped_app();
+        app.set_focus(Absolute::Right);
+        assert_eq!(app.habits[app.focus].name(), "run");
+        app.set_focus(Absolute::Down);
+        assert_eq!(app.habits[app.focus].name(), "read");
+        app.set_focus(Absolute::Down);
+        assert_eq!(app.habits[app.focus].name(), "floss");
+        app.set_focus(Absolute::Up);
+        assert_eq!(app.habits[app.focus].name(), "read");
+    }
+
+    #[test]
+    fn filter_shows_one_group_and_keeps_focus_on_it() {
+        let mut app = grouped_app();
+        app.parse_command("filter learning".parse());
+        assert_eq!(app.rows(), vec![vec![1]]);
+        assert_eq!(app.focus, 1);
+
+        app.parse_command("filter chores".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+
+        app.cycle_filter();
+        assert_eq!(app.filter, None, "learning was the last group");
+        app.cycle_filter();
+        assert_eq!(app.filter.as_deref(), Some("health"));
+        app.parse_command("filter".parse());
+        assert_eq!(app.rows().concat().len(), 4);
+    }
+
+    #[test]
+    fn group_progress_counts_each_group() {
+        let today = Local::now().date_naive();
+        let mut app = grouped_app();
+        app.habits[0].modify(today, TrackEvent::Increment);
+        app.habits[3].modify(today, TrackEvent::Increment);
+        app.habits[3].modify(today, TrackEvent::Increment);
+
+        assert_eq!(
+            app.group_progress(today),
+            vec![
+                ("health".to_owned(), 1, 2),
+                ("learning".to_owned(), 0, 1),
+                ("ungrouped".to_owned(), 0, 1),
+            ]
+        );
+        assert!(
+            app.status()
+                .0
+                .contains("[health 1/2, learning 0/1, ungrouped 0/1]")
+        );
+    }
+
+    #[test]
+    fn add_and_group_commands_set_the_group() {
+        let mut app = App::new();
+        app.parse_command("add gym 3 --group health".parse());
+        assert_eq!(app.habits[0].group(), Some("health"));
+        app.parse_command("group gym".parse());
+        assert_eq!(app.habits[0].group(), None);
+        app.parse_command("group ghost health".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
+    #[test]
+    fn move_and_swap_reorder_and_focus_follows() {
+        let mut app = App::new();
+        for name in ["a", "b", "c"] {
+            app.add_habit(Box::new(Count::new(name, 1)));
+        }
+        app.parse_command("move c 1".parse());
+        assert_eq!(app.list_habits(), ["c", "a", "b"]);
+        app.parse_command("move a 9".parse());
+        assert_eq!(app.list_habits(), ["c", "b", "a"]);
+
+        app.focus = 0;
+        app.swap_focused(Absolute::Right);
+        assert_eq!(app.list_habits(), ["b", "c", "a"]);
+        assert_eq!(app.focus, 1, "focus stays on the moved habit");
+        app.swap_focused(Absolute::Left);
+        app.swap_focused(Absolute::Left);
+        assert_eq!(app.list_habits(), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn sort_orders_by_key_and_keeps_ties_in_place() {
+        let today = Local::now().date_naive();
+        let mut app = grouped_app();
+        app.sort_habits(SortKey::Name, false);
+        assert_eq!(app.list_habits(), ["floss", "gym", "read", "run"]);
+
+        app.sort_habits(SortKey::Group, false);
+        assert_eq!(app.list_habits(), ["gym", "run", "read", "floss"]);
+
+        app.habits[2].modify(today, TrackEvent::Increment);
+        app.sort_habits(SortKey::Remaining, false);
+        assert_eq!(app.list_habits(), ["gym", "run", "floss", "read"]);
+        assert_eq!(app.sort, None);
+    }
+
+    #[test]
+    fn kept_sort_is_saved_with_the_habits() {
+        let mut app = grouped_app();
+        assert!(app.to_habit_file().is_array(), "no order, older format");
+
+        app.parse_command("sort name keep".parse());
+        let file = app.to_habit_file().to_string();
+        let loaded = App::from_habit_file(&file).unwrap();
+        assert_eq!(loaded.sort, Some(SortKey::Name));
+        assert_eq!(loaded.list_habits(), ["floss", "gym", "read", "run"]);
+
+        app.parse_command("move gym 1".parse());
+        assert_eq!(app.sort, None, "a manual move forgets the order");
+    }
+
+    #[test]
+    fn retired_habits_leave_the_grid_but_keep_their_history() {
+        let today = Local::now().date_naive();
+        let mut app = grouped_app();
+        app.habits[1].modify(today, TrackEvent::Increment);
+        app.focus = 3;
+
+        app.parse_command("retire read".parse());
+        assert_eq!(app.list_habits(), ["gym", "floss", "run"]);
+        assert_eq!(app.list_retired(), ["read"]);
+        assert_eq!(app.habits[app.focus].name(), "run", "focus stays put");
+        assert!(!app.status().0.contains("learning"));
+
+        let (title, _) = app.named_dashboard("read").expect("dashboard");
+        assert_eq!(title, "read (retired)");
+
+        app.parse_command("add read 3".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+
+        app.parse_command("unretire read".parse());
+        assert_eq!(app.list_habits(), ["gym", "floss", "run", "read"]);
+        assert_eq!(app.habits[3].remaining(today), 1);
+        assert!(app.list_retired().is_empty());
+    }
+
+    #[test]
+    fn retired_habits_are_saved_apart() {
+        let mut app = grouped_app();
+        app.parse_command("retire floss".parse());
+        let file = app.to_habit_file();
+        assert_eq!(file["retired"][0]["name"], "floss");
+        assert!(file.get("sort").is_none());
+
+        let loaded = App::from_habit_file(&file.to_string()).unwrap();
+        assert_eq!(loaded.list_habits(), ["gym", "read", "run"]);
+        assert_eq!(loaded.list_retired(), ["floss"]);
+
+        app.parse_command("unretire floss".parse());
+        assert!(app.to_habit_file().is_array());
+        app.parse_command("unretire floss".parse());
+        assert!(matches!(app.message.kind(), MessageKind::Error));
+    }
+
     #[test]
     fn describe_unknown_habit_is_an_error() {
         let mut app = App::new();
@@ -665,6 +2154,31 @@ mod tests {
         assert!(matches!(app.message.kind(), MessageKind::Error));
     }
 
+    #[test]
+    fn add_with_a_limit_creates_an_addiction_habit() {
+        let mut app = App::new();
+        app.parse_command("add coffee <2".parse());
+
+        assert_eq!(app.habits.len(), 1);
+        assert_eq!(app.habits[0].kind(), GoalKind::Addiction(2));
+        let (_, body) = app.focused_dashboard().expect("dashboard");
+        assert!(body.source().contains("at most 2 per day"));
+    }
+
+    #[test]
+    fn rating_dashboard_shows_distribution_instead_of_streaks() {
+        let mut app = App::new();
+        let mut habit = Rating::new("mood", 1, 5);
+        Habit::insert_entry(&mut habit, Local::now().date_naive(), 4);
+        app.add_habit(Box::new(habit));
+
+        let (_, body) = app.focused_dashboard().expect("dashboard");
+        let body = body.source();
+        assert!(body.contains("average           4.0"), "body was {body}");
+        assert!(body.contains("Mon"));
+        assert!(!body.contains("current streak"));
+    }
+
     #[test]
     fn month_abbr_is_one_based_and_bounded() {
         assert_eq!(month_abbr(1), "Jan");
@@ -1,6 +1,6 @@
 use cursive::direction::{Absolute, Direction};
 use cursive::event::{Event, EventResult, Key};
-use cursive::theme::Color;
+use cursive::theme::{Color, Effect};
 use cursive::view::{CannotFocus, View};
 use cursive::{Printer, Vec2};
 
@@ -11,12 +11,21 @@ use crate::utils::{GRID_WIDTH, VIEW_HEIGHT, VIEW_WIDTH};
 impl View for App {
     fn draw(&self, printer: &Printer) {
         let mut offset = Vec2::zero();
-        for (idx, habit) in self.habits.iter().enumerate() {
-            if idx >= GRID_WIDTH && idx % GRID_WIDTH == 0 {
-                offset = offset.map_y(|y| y + VIEW_HEIGHT).map_x(|_| 0);
+        let grid_width = GRID_WIDTH * (VIEW_WIDTH + 2);
+        for (header, rows) in self.sections() {
+            if let Some(group) = header {
+                let title = format!("── {group} ");
+                let rule = "─".repeat(grid_width.saturating_sub(title.chars().count()));
+                printer.with_effect(Effect::Bold, |p| p.print(offset, &(title + &rule)));
+                offset = offset.map_y(|y| y + 1);
+            }
+            for row in rows {
+                for (col, idx) in row.into_iter().enumerate() {
+                    let cell = offset.map_x(|_| col * (VIEW_WIDTH + 2));
+                    self.habits[idx].draw(&printer.offset(cell).focused(self.focus == idx));
+                }
+                offset = offset.map_y(|y| y + VIEW_HEIGHT);
             }
-            habit.draw(&printer.offset(offset).focused(self.focus == idx));
-            offset = offset.map_x(|x| x + VIEW_WIDTH + 2);
         }
 
         offset = offset.map_x(|_| 0).map_y(|_| self.max_size().y - 2);
@@ -36,15 +45,7 @@ impl View for App {
 
     fn required_size(&mut self, _: Vec2) -> Vec2 {
         let width = GRID_WIDTH * (VIEW_WIDTH + 2);
-        let height = {
-            if !self.habits.is_empty() {
-                (VIEW_HEIGHT as f64 * (self.habits.len() as f64 / GRID_WIDTH as f64).ceil())
-                    as usize
-            } else {
-                0
-            }
-        };
-        Vec2::new(width, height + 2)
+        Vec2::new(width, self.max_size().y)
     }
 
     fn take_focus(&mut self, _: Direction) -> Result<EventResult, CannotFocus> {
@@ -52,6 +53,18 @@ impl View for App {
     }
 
     fn on_event(&mut self, e: Event) -> EventResult {
+        // undo works on an empty grid too, to bring back a deleted habit
+        match e {
+            Event::Char('u') => {
+                self.undo();
+                return EventResult::Consumed(None);
+            }
+            Event::CtrlChar('r') => {
+                self.redo();
+                return EventResult::Consumed(None);
+            }
+            _ => {}
+        }
         if self.habits.is_empty() {
             return EventResult::Ignored;
         }
@@ -108,6 +121,18 @@ impl View for App {
                 }
                 EventResult::Consumed(None)
             }
+            Event::Char('<') => {
+                self.swap_focused(Absolute::Left);
+                EventResult::Consumed(None)
+            }
+            Event::Char('>') => {
+                self.swap_focused(Absolute::Right);
+                EventResult::Consumed(None)
+            }
+            Event::Char('g') => {
+                self.cycle_filter();
+                EventResult::Consumed(None)
+            }
             Event::Key(Key::Esc) => {
                 for habit in self.habits.iter_mut() {
                     habit.inner_data_mut_ref().set_view_mode(ViewMode::Day);
@@ -120,6 +145,10 @@ impl View for App {
             // push needs the Cursive root, so defer it to a callback
             Event::Char('d') => EventResult::with_cb(crate::command::open_dashboard),
 
+            // type in the focused habit's value for the cursor date; the
+            // prompt lives in the frame, so defer it to a callback
+            Event::Char('=') => EventResult::with_cb(crate::command::open_value_window),
+
             // open the focused habit's description in a popup; the layer push
             // needs the Cursive root, so defer it to a callback
             Event::Char('i') => EventResult::with_cb(crate::command::open_description),
@@ -152,7 +181,14 @@ impl View for App {
                 if self.habits.is_empty() {
                     return EventResult::Ignored;
                 }
-                self.habits[self.focus].on_event(e)
+                // entry edits are undoable like commands
+                let habit = &self.habits[self.focus];
+                let name = habit.name().to_owned();
+                let date = habit.inner_data_ref().cursor().0;
+                let before = self.stored(&name);
+                let result = self.habits[self.focus].on_event(e);
+                self.record_edit(format!("entry of `{name}` on {date}"), &name, before);
+                result
             }
         }
     }
@@ -1,23 +1,56 @@
 use std::default::Default;
 
+use serde::{Deserialize, Serialize};
+
 use crate::habit::HabitWrapper;
 
 mod cursor;
+mod export;
 mod impl_self;
 mod impl_view;
 mod message;
+mod report;
+mod status;
+mod undo;
 
 pub struct StatusLine(String, String);
 pub use cursor::Cursor;
+pub use export::ExportFormat;
 pub use message::{Message, MessageKind};
+pub use report::StatsFormat;
+pub use status::StatusFormat;
+use undo::History;
+pub(crate) use undo::{Change, edited_habit};
 
 pub struct App {
     // holds app data (habit_record.json)
     habits: Vec<Box<dyn HabitWrapper>>,
+    // finished habits, kept for their history but left off the grid
+    retired: Vec<Box<dyn HabitWrapper>>,
 
     focus: usize,
     cursor: Cursor,
     message: Message,
+    // only habits in this group are shown, when set
+    filter: Option<String>,
+    // order re-applied every time the habits are loaded, when set
+    sort: Option<SortKey>,
+    // changes `u` and Ctrl-r step back and forth through
+    history: History,
+}
+
+/// What `:sort` orders habits by. Ties keep their current order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Alphabetically.
+    Name,
+    /// Most left to do today first.
+    Remaining,
+    /// Longest current streak first.
+    Streak,
+    /// Alphabetically by group, ungrouped habits last.
+    Group,
 }
 
 impl Default for App {
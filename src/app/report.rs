@@ -0,0 +1,238 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::app::App;
+use crate::habit::HabitWrapper;
+use crate::stats::HabitStats;
+
+/// How `dijo stats` prints its numbers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsFormat {
+    /// Aligned columns, for reading.
+    Table,
+    Json,
+    /// One header line, then a line per habit and one for all of them.
+    Csv,
+}
+
+/// The stats of one habit, in the unit of its goal period.
+#[derive(Debug, Serialize)]
+struct Row {
+    name: String,
+    /// `day`, `week` or `month`, as in the table and CSV.
+    period: String,
+    #[serde(flatten)]
+    stats: HabitStats,
+}
+
+/// Stats of one or more habits over a span of days, ending with the totals
+/// across them when there is more than one.
+#[derive(Debug)]
+pub struct StatsReport {
+    rows: Vec<Row>,
+    overall: Option<HabitStats>,
+}
+
+impl App {
+    /// Stats of habit `name`, retired ones included, or of every habit on the
+    /// grid, counting the days from `from` (or ever) up to `to`.
+    pub fn stats_report(
+        &self,
+        name: Option<&str>,
+        from: Option<NaiveDate>,
+        to: NaiveDate,
+    ) -> Result<StatsReport, String> {
+        let row = |h: &dyn HabitWrapper| Row {
+            name: h.name().to_owned(),
+            period: h.period().to_string(),
+            stats: h.stats_between(from, to),
+        };
+        let rows: Vec<Row> = match name {
+            Some(name) => {
+                let habit = (self.habits.iter().chain(self.retired.iter()))
+                    .find(|h| h.name() == name)
+                    .ok_or_else(|| format!("Habit `{name}` does not exist"))?;
+                vec![row(habit.as_ref())]
+            }
+            None => self.habits.iter().map(|h| row(h.as_ref())).collect(),
+        };
+        let overall = (name.is_none() && !rows.is_empty()).then(|| totals(&rows));
+        Ok(StatsReport { rows, overall })
+    }
+}
+
+/// Totals across habits: reached periods add up, the streaks are the best
+/// of any habit, and the completion rate is their mean.
+fn totals(rows: &[Row]) -> HabitStats {
+    let stats = rows.iter().map(|r| r.stats);
+    HabitStats {
+        total: stats.clone().map(|s| s.total).sum(),
+        current_streak: stats.clone().map(|s| s.current_streak).max().unwrap_or(0),
+        longest_streak: stats.clone().map(|s| s.longest_streak).max().unwrap_or(0),
+        completion_rate: stats.map(|s| s.completion_rate).sum::<u32>() / rows.len() as u32,
+        freezes_left: None,
+    }
+}
+
+impl StatsReport {
+    pub fn render(&self, format: StatsFormat) -> String {
+        match format {
+            StatsFormat::Table => self.table(),
+            StatsFormat::Json => {
+                json!({ "habits": self.rows, "overall": self.overall }).to_string()
+            }
+            StatsFormat::Csv => self.csv(),
+        }
+    }
+
+    /// Every row as (name, unit, stats), the overall one last as `all`.
+    fn lines(&self) -> Vec<(&str, String, HabitStats)> {
+        let mut lines: Vec<_> = self
+            .rows
+            .iter()
+            .map(|r| (r.name.as_str(), r.period.clone(), r.stats))
+            .collect();
+        if let Some(overall) = self.overall {
+            lines.push(("all", String::new(), overall));
+        }
+        lines
+    }
+
+    fn table(&self) -> String {
+        let width = self.lines().iter().map(|l| l.0.len()).max().unwrap_or(0);
+        let width = width.max("habit".len());
+        let mut out = format!(
+            "{:width$}  {:>5}  {:>5}  {:>7}  {:>7}  {:>4}",
+            "habit", "per", "total", "current", "longest", "rate"
+        );
+        for (name, unit, s) in self.lines() {
+            out.push_str(&format!(
+                "\n{:width$}  {:>5}  {:>5}  {:>7}  {:>7}  {:>3}%",
+                name, unit, s.total, s.current_streak, s.longest_streak, s.completion_rate
+            ));
+        }
+        out
+    }
+
+    fn csv(&self) -> String {
+        let mut out =
+            String::from("habit,period,total,current_streak,longest_streak,completion_rate");
+        for (name, unit, s) in self.lines() {
+            out.push_str(&format!(
+                "\n{},{},{},{},{},{}",
+                csv_field(name),
+                unit,
+                s.total,
+                s.current_streak,
+                s.longest_streak,
+                s.completion_rate
+            ));
+        }
+        out
+    }
+}
+
+/// Quote `field` for CSV when it holds a comma, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::habit::{ActiveRange, Count, TrackEvent};
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 1)));
+        app.add_habit(Box::new(Count::new("read, write", 1)));
+        for day in [1, 2, 3, 5] {
+            app.habits[0].modify(d(day), TrackEvent::Increment);
+        }
+        app.habits[1].modify(d(5), TrackEvent::Increment);
+        app.habits[1]
+            .inner_data_mut_ref()
+            .archived_reached
+            .insert(d(4));
+        app
+    }
+
+    #[test]
+    fn stats_count_archived_days_and_end_at_to() {
+        let report = app().stats_report(None, None, d(5)).unwrap();
+        assert_eq!(report.rows[0].stats.total, 4);
+        assert_eq!(report.rows[0].stats.longest_streak, 3);
+        assert_eq!(report.rows[1].stats.total, 2, "the archived day counts");
+        assert_eq!(report.rows[1].stats.current_streak, 2);
+
+        let report = app().stats_report(Some("gym"), Some(d(2)), d(3)).unwrap();
+        assert_eq!(report.rows[0].stats.total, 2);
+        assert!(report.overall.is_none());
+    }
+
+    #[test]
+    fn from_starts_the_completion_rate_even_before_the_first_reached_day() {
+        let mut app = app();
+        app.habits[1].set_range(ActiveRange::starting(d(1)));
+        let report = app
+            .stats_report(Some("read, write"), Some(d(1)), d(5))
+            .unwrap();
+        assert_eq!(report.rows[0].stats.total, 2);
+        assert_eq!(
+            report.rows[0].stats.completion_rate, 40,
+            "days 1 to 3 were missed"
+        );
+    }
+
+    #[test]
+    fn overall_adds_up_totals_and_keeps_the_best_streaks() {
+        let report = app().stats_report(None, None, d(5)).unwrap();
+        let overall = report.overall.unwrap();
+        assert_eq!(overall.total, 6);
+        assert_eq!(overall.longest_streak, 3);
+        assert_eq!(overall.completion_rate, 90);
+    }
+
+    #[test]
+    fn csv_quotes_names_and_ends_with_all() {
+        let csv = app()
+            .stats_report(None, None, d(5))
+            .unwrap()
+            .render(StatsFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "habit,period,total,current_streak,longest_streak,completion_rate"
+        );
+        assert_eq!(lines[1], "gym,day,4,1,3,80");
+        assert_eq!(lines[2], "\"read, write\",day,2,2,2,100");
+        assert_eq!(lines[3], "all,,6,2,3,90");
+    }
+
+    #[test]
+    fn json_holds_each_habit_and_the_overall_stats() {
+        let json = app()
+            .stats_report(None, None, d(5))
+            .unwrap()
+            .render(StatsFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["habits"][0]["name"], "gym");
+        assert_eq!(json["habits"][0]["period"], "day");
+        assert_eq!(json["habits"][0]["completion_rate"], 80);
+        assert_eq!(json["overall"]["total"], 6);
+    }
+
+    #[test]
+    fn unknown_habits_are_an_error() {
+        assert!(app().stats_report(Some("swim"), None, d(5)).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::CONFIGURATION;
+use crate::app::App;
+use crate::command::GoalKind;
+use crate::habit::HabitWrapper;
+
+/// The line `dijo status` prints when no template is given.
+const DEFAULT_TEMPLATE: &str = "{completed} completed, {remaining} remaining";
+
+/// How `dijo status` prints the day's progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusFormat {
+    /// The summary line, then one line per habit due that day.
+    Plain,
+    Json,
+    /// A custom module for waybar, with `return-type` set to `json`.
+    Waybar,
+    /// Full text, short text and, once everything is done, a colour.
+    I3blocks,
+}
+
+impl FromStr for StatusFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "plain" => Ok(StatusFormat::Plain),
+            "json" => Ok(StatusFormat::Json),
+            "waybar" => Ok(StatusFormat::Waybar),
+            "i3blocks" => Ok(StatusFormat::I3blocks),
+            _ => Err(format!("`{s}` is not plain, json, waybar or i3blocks")),
+        }
+    }
+}
+
+/// Progress on one day, overall and per habit. Overall, each habit due that
+/// day counts once, done or not, whatever its unit.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    date: NaiveDate,
+    /// Habits due and done.
+    completed: u32,
+    remaining: u32,
+    /// Habits due.
+    total: u32,
+    habits: Vec<HabitSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct HabitSummary {
+    name: String,
+    group: Option<String>,
+    due: bool,
+    done: bool,
+    /// What is left, in the habit's unit: decimals for number habits and
+    /// minutes for time habits.
+    remaining: Value,
+    /// What is left as the habit writes it, e.g. `1h30`.
+    #[serde(skip)]
+    shown: String,
+}
+
+impl App {
+    /// Progress of every habit on the grid on `date`, whatever the filter.
+    pub fn summary(&self, date: NaiveDate) -> Summary {
+        let habits: Vec<HabitSummary> = self
+            .habits
+            .iter()
+            .map(|h| habit_summary(h.as_ref(), date))
+            .collect();
+        let indices: Vec<usize> = (0..self.habits.len()).collect();
+        let (completed, total) = self.progress(indices.iter(), date);
+        Summary {
+            date,
+            completed,
+            remaining: total - completed,
+            total,
+            habits,
+        }
+    }
+}
+
+fn habit_summary(habit: &dyn HabitWrapper, date: NaiveDate) -> HabitSummary {
+    let due = habit.is_due(date);
+    let left = if due { habit.remaining(date) } else { 0 };
+    let remaining = match habit.kind() {
+        GoalKind::Float(_, precision) => json!(left as f64 / 10f64.powi(precision as i32)),
+        _ => json!(left),
+    };
+    HabitSummary {
+        name: habit.name().to_owned(),
+        group: habit.group().map(str::to_owned),
+        due,
+        done: due && left == 0,
+        remaining,
+        shown: habit.format_value(left),
+    }
+}
+
+impl Summary {
+    /// Share of the habits due that day that are done, in percent; 100 with
+    /// nothing due.
+    fn percent(&self) -> u32 {
+        (self.completed * 100)
+            .checked_div(self.total)
+            .unwrap_or(100)
+    }
+
+    /// Due habits that still have something left, comma-separated.
+    fn todo(&self) -> String {
+        let todo: Vec<&str> = self
+            .habits
+            .iter()
+            .filter(|h| h.due && !h.done)
+            .map(|h| h.name.as_str())
+            .collect();
+        todo.join(", ")
+    }
+
+    /// One line per due habit: what is left of it, or that it is done.
+    fn habit_lines(&self) -> Vec<String> {
+        self.habits
+            .iter()
+            .filter(|h| h.due)
+            .map(|h| match h.done {
+                true => format!("{}: done", h.name),
+                false => format!("{}: {} remaining", h.name, h.shown),
+            })
+            .collect()
+    }
+
+    /// Fill in `template`: `{completed}`, `{remaining}`, `{total}`,
+    /// `{percent}`, `{todo}`, `{date}`, or a habit's name for what is left of
+    /// it. Anything else in braces is kept as written.
+    pub fn fill(&self, template: &str) -> String {
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}') else {
+                break;
+            };
+            out.push_str(&rest[..open]);
+            let key = &rest[open + 1..open + close];
+            let value = match key {
+                "completed" => Some(self.completed.to_string()),
+                "remaining" => Some(self.remaining.to_string()),
+                "total" => Some(self.total.to_string()),
+                "percent" => Some(self.percent().to_string()),
+                "todo" => Some(self.todo()),
+                "date" => Some(self.date.to_string()),
+                name => (self.habits.iter())
+                    .find(|h| h.name == name)
+                    .map(|h| h.shown.clone()),
+            };
+            match value {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&rest[open..=open + close]),
+            }
+            rest = &rest[open + close + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// The whole output of `dijo status` in `format`. The template shapes
+    /// the main line of every format but JSON.
+    pub fn render(&self, format: StatusFormat, template: Option<&str>) -> String {
+        let line = self.fill(template.unwrap_or(DEFAULT_TEMPLATE));
+        let all_done = self.completed == self.total;
+        match format {
+            StatusFormat::Plain if template.is_some() => line,
+            StatusFormat::Plain => {
+                let mut lines = vec![line];
+                lines.extend(self.habit_lines());
+                lines.join("\n")
+            }
+            StatusFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+            StatusFormat::Waybar => json!({
+                "text": line,
+                "tooltip": self.habit_lines().join("\n"),
+                "class": if all_done { "done" } else { "pending" },
+                "percentage": self.percent(),
+            })
+            .to_string(),
+            StatusFormat::I3blocks => {
+                let mut lines = vec![line, format!("{}/{}", self.completed, self.total)];
+                if all_done && let Some(color) = CONFIGURATION.reached_hex() {
+                    lines.push(color);
+                }
+                lines.join("\n")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::habit::{Addiction, Count, Duration, Float, TrackEvent};
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 3).unwrap()
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 2)));
+        app.add_habit(Box::new(Duration::new("read", 90)));
+        app.add_habit(Box::new(Float::new("run", 25, 1)));
+        app.habits[0].modify(day(), TrackEvent::Set(2));
+        app.habits[1].modify(day(), TrackEvent::Set(30));
+        app
+    }
+
+    #[test]
+    fn templates_fill_totals_and_habits() {
+        let summary = app().summary(day());
+        assert_eq!(
+            summary.fill("{completed}/{total} {percent}% | read {read} | {todo} {nope}"),
+            "1/3 33% | read 1h | read, run {nope}"
+        );
+        assert_eq!(summary.fill("{todo} {open"), "read, run {open");
+    }
+
+    #[test]
+    fn a_limit_kept_so_far_is_done_today() {
+        let mut app = App::new();
+        app.add_habit(Box::new(Addiction::new("coffee", 2)));
+        let today = chrono::Local::now().date_naive();
+        app.habits[0].modify(today, TrackEvent::Set(1));
+        let summary = app.summary(today);
+        assert_eq!(summary.fill("{completed}/{total}"), "1/1");
+        assert_eq!(summary.percent(), 100);
+    }
+
+    #[test]
+    fn plain_lists_each_due_habit() {
+        let out = app().summary(day()).render(StatusFormat::Plain, None);
+        assert_eq!(
+            out,
+            "1 completed, 2 remaining\ngym: done\nread: 1h remaining\nrun: 2.5 remaining"
+        );
+    }
+
+    #[test]
+    fn json_gives_remaining_in_each_habits_unit() {
+        let out = app().summary(day()).render(StatusFormat::Json, None);
+        let json: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["remaining"], 2);
+        assert_eq!(json["habits"][0]["done"], true);
+        assert_eq!(json["habits"][1]["remaining"], 60);
+        assert_eq!(json["habits"][2]["remaining"], 2.5);
+    }
+
+    #[test]
+    fn waybar_and_i3blocks_use_the_template() {
+        let summary = app().summary(day());
+        let waybar = summary.render(StatusFormat::Waybar, Some("{todo}"));
+        let waybar: Value = serde_json::from_str(&waybar).unwrap();
+        assert_eq!(waybar["text"], "read, run");
+        assert_eq!(waybar["class"], "pending");
+
+        let i3 = summary.render(StatusFormat::I3blocks, None);
+        assert_eq!(i3, "1 completed, 2 remaining\n1/3");
+
+        let mut app = app();
+        app.habits.truncate(1);
+        let i3 = app.summary(day()).render(StatusFormat::I3blocks, None);
+        let colour = CONFIGURATION.reached_hex().map(|c| format!("\n{c}"));
+        assert_eq!(
+            i3,
+            format!(
+                "1 completed, 0 remaining\n1/1{}",
+                colour.unwrap_or_default()
+            )
+        );
+    }
+}
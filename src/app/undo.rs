@@ -0,0 +1,273 @@
+use serde_json::Value;
+
+use crate::app::{App, MessageKind};
+use crate::command::Command;
+use crate::habit::HabitWrapper;
+
+/// How many changes `u` can step back through; older ones are forgotten.
+const UNDO_LIMIT: usize = 100;
+
+/// A change to the habits, held as what it takes to go back on it. Reverting
+/// one gives the change that goes back on *that*, which is how a change moves
+/// between the undo and redo stacks.
+pub enum Change {
+    /// Habit `name` was edited; `state` is how it was stored before.
+    Edited { name: String, state: Value },
+    /// Habit `name` was put on the grid, or among the retired habits.
+    Inserted { name: String, retired: bool },
+    /// `habit` was taken from `index` of the grid, or of the retired habits.
+    Removed {
+        habit: Box<dyn HabitWrapper>,
+        index: usize,
+        retired: bool,
+    },
+}
+
+/// A `Change` along with what it was, e.g. "delete `gym`", for the message
+/// line.
+struct Step {
+    what: String,
+    change: Change,
+}
+
+/// Changes that can be undone, most recent last, and those undone since the
+/// last new change, which can be redone.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+}
+
+impl App {
+    /// Remember `change` as undoable. A new change can't be redone past, so
+    /// whatever was undone before it is forgotten.
+    pub(crate) fn record(&mut self, what: String, change: Change) {
+        self.history.redo.clear();
+        self.history.undo.push(Step { what, change });
+        if self.history.undo.len() > UNDO_LIMIT {
+            self.history.undo.remove(0);
+        }
+    }
+
+    /// Forget every change that could be undone or redone.
+    pub(crate) fn forget_history(&mut self) {
+        self.history = History::default();
+    }
+
+    /// How habit `name` is stored right now, to tell whether an edit changed
+    /// it and to restore it from.
+    pub(crate) fn stored(&self, name: &str) -> Option<Value> {
+        let habit = self.habits.iter().find(|h| h.name() == name)?;
+        serde_json::to_value(habit).ok()
+    }
+
+    /// Record an edit of habit `name`, which was stored as `before`, unless it
+    /// left the habit as it was.
+    pub(crate) fn record_edit(&mut self, what: String, name: &str, before: Option<Value>) {
+        let Some(before) = before else {
+            return;
+        };
+        if self.stored(name).is_some_and(|after| after != before) {
+            let name = name.to_owned();
+            self.record(
+                what,
+                Change::Edited {
+                    name,
+                    state: before,
+                },
+            );
+        }
+    }
+
+    pub fn undo(&mut self) {
+        let Some(step) = self.history.undo.pop() else {
+            self.message.set_kind(MessageKind::Info);
+            self.message.set_message("Nothing to undo");
+            return;
+        };
+        match self.revert(step.change) {
+            Some(change) => {
+                self.message.set_kind(MessageKind::Info);
+                self.message.set_message(format!("Undid {}", step.what));
+                self.history.redo.push(Step {
+                    what: step.what,
+                    change,
+                });
+            }
+            None => {
+                self.message.set_kind(MessageKind::Error);
+                self.message
+                    .set_message(format!("Could not undo {}", step.what));
+            }
+        }
+    }
+
+    pub fn redo(&mut self) {
+        let Some(step) = self.history.redo.pop() else {
+            self.message.set_kind(MessageKind::Info);
+            self.message.set_message("Nothing to redo");
+            return;
+        };
+        match self.revert(step.change) {
+            Some(change) => {
+                self.message.set_kind(MessageKind::Info);
+                self.message.set_message(format!("Redid {}", step.what));
+                self.history.undo.push(Step {
+                    what: step.what,
+                    change,
+                });
+            }
+            None => {
+                self.message.set_kind(MessageKind::Error);
+                self.message
+                    .set_message(format!("Could not redo {}", step.what));
+            }
+        }
+    }
+
+    /// Go back on `change`, returning the change that goes back on that.
+    /// `None` when the habit it applies to is gone, e.g. renamed since.
+    fn revert(&mut self, change: Change) -> Option<Change> {
+        let reverted = match change {
+            Change::Edited { name, state } => {
+                let index = self.habits.iter().position(|h| h.name() == name)?;
+                let current = serde_json::to_value(&self.habits[index]).ok()?;
+                let mut restored: Box<dyn HabitWrapper> = serde_json::from_value(state).ok()?;
+                let old = &mut self.habits[index];
+                *restored.inner_data_mut_ref() = std::mem::take(old.inner_data_mut_ref());
+                *old = restored;
+                Change::Edited {
+                    name,
+                    state: current,
+                }
+            }
+            Change::Inserted { name, retired } => {
+                let list = if retired {
+                    &mut self.retired
+                } else {
+                    &mut self.habits
+                };
+                let index = list.iter().position(|h| h.name() == name)?;
+                Change::Removed {
+                    habit: list.remove(index),
+                    index,
+                    retired,
+                }
+            }
+            Change::Removed {
+                habit,
+                index,
+                retired,
+            } => {
+                let list = if retired {
+                    &mut self.retired
+                } else {
+                    &mut self.habits
+                };
+                let name = habit.name().to_owned();
+                list.insert(index.min(list.len()), habit);
+                Change::Inserted { name, retired }
+            }
+        };
+        self.focus = self.focus.min(self.habits.len().saturating_sub(1));
+        self.focus_visible();
+        Some(reverted)
+    }
+}
+
+/// What a command that edits a single habit does, and to which habit, so
+/// that the edit can be recorded. `None` for every other command.
+pub(crate) fn edited_habit(command: &Command) -> Option<(String, String)> {
+    let (what, name) = match command {
+        Command::Describe(name, _) => ("description", name),
+        Command::Set(name, _, _) => ("entry", name),
+        Command::Step(name, _) => ("step", name),
+        Command::Goal(name, _, _) => ("goal", name),
+        Command::Convert(name, _, _) => ("conversion", name),
+        _ => return None,
+    };
+    Some((format!("{what} of `{name}`"), name.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::habit::Count;
+    use chrono::Local;
+    use cursive::event::Event;
+    use cursive::view::View;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_habit(Box::new(Count::new("gym", 2)));
+        app.add_habit(Box::new(Count::new("read", 1)));
+        app
+    }
+
+    #[test]
+    fn a_deleted_habit_comes_back_where_it_was() {
+        let mut app = app();
+        app.parse_command("delete gym".parse());
+        assert_eq!(app.list_habits(), ["read"]);
+
+        app.undo();
+        assert_eq!(app.list_habits(), ["gym", "read"]);
+        assert_eq!(app.message.contents(), "Undid delete `gym`");
+
+        app.redo();
+        assert_eq!(app.list_habits(), ["read"]);
+        assert_eq!(app.message.contents(), "Redid delete `gym`");
+    }
+
+    #[test]
+    fn undoing_an_add_removes_the_habit() {
+        let mut app = app();
+        app.parse_command("add run 5".parse());
+        app.undo();
+        assert_eq!(app.list_habits(), ["gym", "read"]);
+        app.redo();
+        assert_eq!(app.list_habits(), ["gym", "read", "run"]);
+    }
+
+    #[test]
+    fn entry_edits_and_goal_changes_are_undone() {
+        let mut app = app();
+        let today = Local::now().date_naive();
+        app.on_event(Event::Char('n'));
+        app.parse_command("goal gym 5".parse());
+        assert_eq!(app.habits[0].goal(), 5);
+
+        app.on_event(Event::Char('u'));
+        assert_eq!(app.habits[0].goal(), 2);
+        assert_eq!(app.habits[0].remaining(today), 1, "the entry stays");
+
+        app.undo();
+        assert_eq!(app.habits[0].remaining(today), 2);
+        assert_eq!(
+            app.message.contents(),
+            format!("Undid entry of `gym` on {today}")
+        );
+        app.on_event(Event::CtrlChar('r'));
+        assert_eq!(app.habits[0].remaining(today), 1);
+    }
+
+    #[test]
+    fn commands_that_change_nothing_are_not_recorded() {
+        let mut app = app();
+        app.parse_command("goal swim 5".parse());
+        app.parse_command("describe gym".parse());
+        app.undo();
+        assert_eq!(app.message.contents(), "Nothing to undo");
+    }
+
+    #[test]
+    fn a_new_change_forgets_what_was_undone() {
+        let mut app = app();
+        app.parse_command("describe gym legs".parse());
+        app.undo();
+        app.parse_command("describe read a chapter".parse());
+        app.redo();
+        assert_eq!(app.message.contents(), "Nothing to redo");
+        assert_eq!(app.habits[0].description(), "");
+    }
+}
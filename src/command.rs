@@ -1,19 +1,39 @@
 use std::fmt;
 use std::str::FromStr;
 
+use chrono::Weekday::{self, *};
+use chrono::{Local, NaiveDate};
 use cursive::Cursive;
 use cursive::event::{Event, EventResult, Key};
 use cursive::theme::{BaseColor, Color, ColorStyle};
+use cursive::utils::markup::StyledString;
 use cursive::view::Resizable;
 use cursive::views::{Dialog, EditView, LinearLayout, OnEventView, TextView};
 
-use crate::app::App;
-use crate::habit::GoalPeriod;
+use crate::app::{App, SortKey};
+use crate::habit::{ActiveRange, Freezes, GoalPeriod, Pause, Schedule};
 use crate::utils::{GRID_WIDTH, VIEW_WIDTH};
 
 static COMMANDS: &[&str] = &[
     "add",
     "describe",
+    "note",
+    "schedule",
+    "set",
+    "range",
+    "pause",
+    "unpause",
+    "freeze",
+    "convert",
+    "rename",
+    "group",
+    "filter",
+    "move",
+    "sort",
+    "step",
+    "retire",
+    "unretire",
+    "goal",
     "delete",
     "month-prev",
     "month-next",
@@ -36,8 +56,13 @@ fn get_habit_completion(prefix: &str, habit_names: &[String]) -> Option<String>
 }
 
 pub fn open_command_window(s: &mut Cursive) {
+    // retired habits complete too, for `:unretire` and `:dashboard`
     let habit_list: Vec<String> = s
-        .call_on_name("Main", |view: &mut App| view.list_habits())
+        .call_on_name("Main", |view: &mut App| {
+            let mut names = view.list_habits();
+            names.extend(view.list_retired());
+            names
+        })
         .unwrap();
     let style = ColorStyle::new(Color::Dark(BaseColor::Black), Color::Dark(BaseColor::White));
     let command_window = OnEventView::new(
@@ -79,6 +104,50 @@ pub fn open_command_window(s: &mut Cursive) {
     });
 }
 
+/// Open an inline prompt below the grid for the focused habit's value on the
+/// cursor date, e.g. `8.5` or `1h30`. Submitting it empty changes nothing.
+pub fn open_value_window(s: &mut Cursive) {
+    let Some((name, date)) = s
+        .call_on_name("Main", |view: &mut App| view.focused_entry())
+        .flatten()
+    else {
+        return;
+    };
+    let prompt = format!("{name} on {date} = ");
+    let style = ColorStyle::new(Color::Dark(BaseColor::Black), Color::Dark(BaseColor::White));
+    let value_window = EditView::new()
+        .filler(" ")
+        .on_submit(move |s, input| {
+            s.call_on_name("Main", |view: &mut App| {
+                view.clear_message();
+                if input.trim().is_empty() {
+                    return;
+                }
+                let value = input.trim().to_owned();
+                view.parse_command(Ok(Command::Set(name.clone(), value, Some(date))));
+            });
+            s.call_on_name("Frame", |view: &mut LinearLayout| {
+                let _ = view.set_focus_index(0);
+                view.remove_child(view.get_focus_index());
+            });
+        })
+        .style(style)
+        // a long habit name still leaves room to type a value like `1h30`
+        .fixed_width(
+            (VIEW_WIDTH * GRID_WIDTH)
+                .saturating_sub(prompt.chars().count())
+                .max(8),
+        );
+    s.call_on_name("Frame", |view: &mut LinearLayout| {
+        let mut valueline = LinearLayout::horizontal()
+            .child(TextView::new(prompt))
+            .child(value_window);
+        let _ = valueline.set_focus_index(1);
+        view.add_child(valueline);
+        let _ = view.set_focus_index(1);
+    });
+}
+
 fn call_on_app(s: &mut Cursive, input: &str) {
     // things to do after recieving the command
     // 1. parse the command
@@ -101,7 +170,15 @@ fn call_on_app(s: &mut Cursive, input: &str) {
     // TODO: fix this somehow
     match input.parse::<Command>() {
         Ok(Command::Quit) | Ok(Command::WriteAndQuit) => s.quit(),
-        Ok(Command::Dashboard) => open_dashboard(s),
+        Ok(Command::Dashboard(None)) => open_dashboard(s),
+        Ok(Command::Dashboard(Some(name))) => {
+            let dashboard = s
+                .call_on_name("Main", |view: &mut App| view.named_dashboard(&name))
+                .flatten();
+            if let Some((title, body)) = dashboard {
+                show_dashboard(s, title, body);
+            }
+        }
         _ => {}
     }
 }
@@ -112,12 +189,14 @@ pub fn open_dashboard(s: &mut Cursive) {
     let dashboard = s
         .call_on_name("Main", |view: &mut App| view.focused_dashboard())
         .flatten();
-    let Some((name, body)) = dashboard else {
-        return;
-    };
+    if let Some((name, body)) = dashboard {
+        show_dashboard(s, name, body);
+    }
+}
 
+fn show_dashboard(s: &mut Cursive, title: String, body: StyledString) {
     let dialog = Dialog::around(TextView::new(body))
-        .title(name)
+        .title(title)
         .button("close", |s| {
             s.pop_layer();
         });
@@ -162,17 +241,32 @@ pub enum GoalKind {
     Bit,
     Float(u32, u8),
     Addiction(u32),
+    Duration(u32),
+    /// State labels, each paired with whether it counts as a success.
+    Cycle(Vec<(String, bool)>),
+    /// Lowest and highest value of the scale.
+    Rating(u32, u32),
 }
 
 impl FromStr for GoalKind {
     type Err = CommandLineError;
 
     fn from_str(s: &str) -> Result<Self> {
-        if let Some(n) = s.strip_prefix("<") {
+        if let Some(spec) = s.strip_prefix("cycle:") {
+            return parse_cycle(spec)
+                .ok_or_else(|| CommandLineError::InvalidGoal(s.into()))
+                .map(GoalKind::Cycle);
+        } else if let Some(spec) = s.strip_prefix("rating:") {
+            return parse_rating(spec)
+                .ok_or_else(|| CommandLineError::InvalidGoal(s.into()))
+                .map(|(min, max)| GoalKind::Rating(min, max));
+        } else if let Some(n) = s.strip_prefix("<") {
             return n
                 .parse::<u32>()
                 .map_err(|_| CommandLineError::InvalidGoal(s.into()))
                 .map(GoalKind::Addiction);
+        } else if let Some(minutes) = parse_minutes(s) {
+            return Ok(GoalKind::Duration(minutes));
         } else if s.contains(".") {
             let value = s
                 .chars()
@@ -194,21 +288,171 @@ impl FromStr for GoalKind {
     }
 }
 
-/// Split a trailing period marker (`/week`, `/weekly`, `/w`) off a goal token,
-/// returning the bare goal expression and the period it denotes.
+/// Parse the comma-separated states of a `cycle:` goal. A state ending in `*`
+/// is a success; when none is marked, the last state is the only success.
+/// Needs at least two distinct, non-empty states.
+fn parse_cycle(spec: &str) -> Option<Vec<(String, bool)>> {
+    let mut states: Vec<(String, bool)> = spec
+        .split(',')
+        .map(|s| match s.strip_suffix('*') {
+            Some(label) => (label.to_owned(), true),
+            None => (s.to_owned(), false),
+        })
+        .collect();
+    if states.len() < 2 || states.iter().any(|(label, _)| label.is_empty()) {
+        return None;
+    }
+    for (i, (label, _)) in states.iter().enumerate() {
+        if states[..i].iter().any(|(other, _)| other == label) {
+            return None;
+        }
+    }
+    if !states.iter().any(|(_, success)| *success) {
+        states.last_mut()?.1 = true;
+    }
+    Some(states)
+}
+
+/// Parse the scale of a `rating:` goal, written `LO-HI` or just `HI` for a
+/// scale starting at 1. The scale must span at least two values.
+fn parse_rating(spec: &str) -> Option<(u32, u32)> {
+    let (min, max) = match spec.split_once('-') {
+        Some((lo, hi)) => (lo.parse().ok()?, hi.parse().ok()?),
+        None => (1, spec.parse().ok()?),
+    };
+    (min < max).then_some((min, max))
+}
+
+/// Parse a weekday schedule such as `mon,wed,fri`, `weekdays` or `weekends`.
+/// Days may be abbreviated or spelled out, in any case. `every:3` (or
+/// `every 3 days`) makes the habit due 3 days after it was last done.
+fn parse_schedule(spec: &str) -> Option<Schedule> {
+    if let Some(rest) = spec.to_lowercase().strip_prefix("every") {
+        let rest = rest.trim_start_matches([':', ',']);
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let every = rest[..end].parse::<u32>().ok().filter(|&n| n > 0)?;
+        return match rest[end..].trim_start_matches(',') {
+            "" | "d" | "day" | "days" => Some(Schedule::every(every)),
+            _ => None,
+        };
+    }
+    let mut days = Vec::new();
+    for token in spec.split(',').filter(|t| !t.is_empty()) {
+        match token.to_lowercase().as_str() {
+            "weekdays" => days.extend([Mon, Tue, Wed, Thu, Fri]),
+            "weekends" => days.extend([Sat, Sun]),
+            "daily" | "everyday" => days.extend([Mon, Tue, Wed, Thu, Fri, Sat, Sun]),
+            day => days.push(day.parse::<Weekday>().ok()?),
+        }
+    }
+    Some(Schedule::new(days))
+}
+
+/// Parse a span of time written as `45m`, `45min`, `1h`, `1h30`, `1h30m` or
+/// `1:30` into minutes. Bare numbers are not durations and yield `None`.
+pub fn parse_minutes(s: &str) -> Option<u32> {
+    let (hours, minutes) = if let Some((h, m)) = s.split_once(':') {
+        (h, m)
+    } else if let Some((h, m)) = s.split_once('h') {
+        (h, m.strip_suffix('m').unwrap_or(m))
+    } else {
+        let m = s.strip_suffix("min").or_else(|| s.strip_suffix('m'))?;
+        ("0", m)
+    };
+    let hours = hours.parse::<u32>().ok()?;
+    // only `1h` may leave the minutes out; `m` or `1:` on its own is no time
+    let minutes = match minutes {
+        "" if s.contains('h') => 0,
+        m => m.parse::<u32>().ok()?,
+    };
+    if hours > 0 && minutes >= 60 {
+        return None;
+    }
+    hours.checked_mul(60)?.checked_add(minutes)
+}
+
+/// Parse a goal expression with its optional period suffix, as taken by
+/// `add` and `goal`.
+fn parse_goal(raw: &str) -> Result<(GoalKind, GoalPeriod)> {
+    let (base, period) = split_period(raw);
+    let mut kind = GoalKind::from_str(base)?;
+    // A weekly `1` is "once a week", a legitimate count target,
+    // not a daily yes/no habit; likewise a monthly one.
+    if period != GoalPeriod::Daily && kind == GoalKind::Bit {
+        kind = GoalKind::Count(1);
+    }
+    // Limits, cycle states and ratings are judged one day at a time.
+    if period != GoalPeriod::Daily
+        && matches!(
+            kind,
+            GoalKind::Addiction(_) | GoalKind::Cycle(_) | GoalKind::Rating(..)
+        )
+    {
+        return Err(CommandLineError::InvalidGoal(raw.to_owned()));
+    }
+    Ok((kind, period))
+}
+
+/// Parse a date written `YYYY-MM-DD`, `today` or `yesterday`.
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    let today = Local::now().date_naive();
+    match s.to_lowercase().as_str() {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        s => NaiveDate::parse_from_str(s, "%Y-%m-%d").ok(),
+    }
+}
+
+/// Split a trailing period marker (`/week`, `/weekly`, `/w`, or `/month`,
+/// `/monthly`, `/m`) off a goal token, returning the bare goal expression and
+/// the period it denotes.
 fn split_period(token: &str) -> (&str, GoalPeriod) {
     for suffix in ["/week", "/weekly", "/w"] {
         if let Some(base) = token.strip_suffix(suffix) {
             return (base, GoalPeriod::Weekly);
         }
     }
+    for suffix in ["/month", "/monthly", "/m"] {
+        if let Some(base) = token.strip_suffix(suffix) {
+            return (base, GoalPeriod::Monthly);
+        }
+    }
     (token, GoalPeriod::Daily)
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Command {
-    Add(String, Option<GoalKind>, GoalPeriod),
+    /// Name, goal, its period, group and step; the step is kept as typed and
+    /// read by the new habit, which knows its unit.
+    Add(
+        String,
+        Option<GoalKind>,
+        GoalPeriod,
+        Option<String>,
+        Option<String>,
+    ),
     Describe(String, String),
+    Note(String, String),
+    Set(String, String, Option<NaiveDate>),
+    Step(String, String),
+    Schedule(String, Schedule),
+    Range(String, ActiveRange),
+    Pause(String, Pause),
+    /// Unpause the days of a span, or every paused day with `None`.
+    Unpause(String, Option<Pause>),
+    ImportPauses(String, String),
+    Freeze(String, Option<Freezes>),
+    Goal(String, GoalKind, GoalPeriod),
+    Convert(String, GoalKind, GoalPeriod),
+    Rename(String, String),
+    Group(String, Option<String>),
+    Filter(Option<String>),
+    Move(String, usize),
+    Retire(String),
+    Unretire(String),
+    Sort(Option<SortKey>, bool),
     MonthPrev,
     MonthNext,
     Delete(String),
@@ -218,7 +462,7 @@ pub enum Command {
     Blank,
     WriteAndQuit,
     Archive,
-    Dashboard,
+    Dashboard(Option<String>),
 }
 
 #[derive(Debug)]
@@ -258,18 +502,33 @@ impl FromStr for Command {
         let first = strings.first().unwrap().to_string();
         let mut args: Vec<String> = strings.iter_mut().skip(1).map(|s| s.to_string()).collect();
         let mut _add = |first: String| {
+            // `--group <name>` may appear anywhere after the habit name
+            let group = match args.iter().position(|a| a == "--group") {
+                Some(i) if i + 1 < args.len() => {
+                    let group = args.remove(i + 1);
+                    args.remove(i);
+                    Some(group)
+                }
+                Some(_) => return Err(CommandLineError::NotEnoughArgs(first, 3)),
+                None => None,
+            };
+            // and so may `step:<n>`
+            let step = match args.iter().position(|a| a.starts_with("step:")) {
+                Some(i) => {
+                    let step = args.remove(i)["step:".len()..].to_owned();
+                    if step.is_empty() {
+                        return Err(CommandLineError::InvalidArg(i as u32 + 1));
+                    }
+                    Some(step)
+                }
+                None => None,
+            };
             if args.is_empty() {
                 return Err(CommandLineError::NotEnoughArgs(first, 1));
             }
             let (goal, period) = match args.get(1) {
                 Some(raw) => {
-                    let (base, period) = split_period(raw);
-                    let mut kind = GoalKind::from_str(base)?;
-                    // A weekly `1` is "once a week", a legitimate count target,
-                    // not a daily yes/no habit.
-                    if period == GoalPeriod::Weekly && kind == GoalKind::Bit {
-                        kind = GoalKind::Count(1);
-                    }
+                    let (kind, period) = parse_goal(raw)?;
                     (Some(kind), period)
                 }
                 None => (None, GoalPeriod::Daily),
@@ -278,6 +537,8 @@ impl FromStr for Command {
                 args.get_mut(0).unwrap().to_string(),
                 goal,
                 period,
+                group,
+                step,
             ))
         };
 
@@ -299,6 +560,180 @@ impl FromStr for Command {
                 let description = args[1..].join(" ").replace("\\n", "\n");
                 Ok(Command::Describe(name, description))
             }
+            "note" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                // a bare `note <habit>` clears the note on the cursor date
+                Ok(Command::Note(args[0].to_string(), args[1..].join(" ")))
+            }
+            "set" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                // the value is read by the habit itself, which knows its unit
+                let date = match args.get(2) {
+                    Some(raw) => Some(parse_date(raw).ok_or(CommandLineError::InvalidArg(3))?),
+                    None => None,
+                };
+                Ok(Command::Set(args[0].to_string(), args[1].to_string(), date))
+            }
+            "step" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                Ok(Command::Step(args[0].to_string(), args[1].to_string()))
+            }
+            "schedule" | "sched" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                // days may be given comma- or space-separated; none clears it
+                let spec = args[1..].join(",");
+                let schedule = parse_schedule(&spec).ok_or(CommandLineError::InvalidArg(2))?;
+                Ok(Command::Schedule(args[0].to_string(), schedule))
+            }
+            "range" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                let start = parse_date(&args[1]).ok_or(CommandLineError::InvalidArg(2))?;
+                // leaving out the end keeps the habit open-ended
+                let end = match args.get(2) {
+                    Some(raw) => Some(
+                        parse_date(raw)
+                            .filter(|&end| end >= start)
+                            .ok_or(CommandLineError::InvalidArg(3))?,
+                    ),
+                    None => None,
+                };
+                let range = ActiveRange {
+                    start: Some(start),
+                    end,
+                };
+                Ok(Command::Range(args[0].to_string(), range))
+            }
+            "goal" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                let (kind, period) = parse_goal(&args[1])?;
+                Ok(Command::Goal(args[0].to_string(), kind, period))
+            }
+            "group" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                // a bare `group <habit>` takes it out of its group
+                let group = args.get(1).filter(|g| !g.is_empty()).cloned();
+                Ok(Command::Group(args[0].to_string(), group))
+            }
+            "filter" => Ok(Command::Filter(
+                args.first().filter(|g| !g.is_empty()).cloned(),
+            )),
+            "move" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                let position = args[1]
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&p| p > 0)
+                    .ok_or(CommandLineError::InvalidArg(2))?;
+                Ok(Command::Move(args[0].to_string(), position))
+            }
+            "sort" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                // `none` forgets a remembered order without reordering
+                let key = match args[0].as_str() {
+                    "name" => Some(SortKey::Name),
+                    "remaining" => Some(SortKey::Remaining),
+                    "streak" => Some(SortKey::Streak),
+                    "group" => Some(SortKey::Group),
+                    "none" => None,
+                    _ => return Err(CommandLineError::InvalidArg(1)),
+                };
+                let keep = match args.get(1).map(String::as_str) {
+                    None => false,
+                    Some("keep") => true,
+                    Some(_) => return Err(CommandLineError::InvalidArg(2)),
+                };
+                Ok(Command::Sort(key, keep))
+            }
+            "rename" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                Ok(Command::Rename(args[0].to_string(), args[1].to_string()))
+            }
+            "convert" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                let (kind, period) = parse_goal(&args[1])?;
+                Ok(Command::Convert(args[0].to_string(), kind, period))
+            }
+            "freeze" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                // `2/week`; no allowance, or `0`, turns freezes off
+                let freezes = match args.get(1) {
+                    Some(raw) => {
+                        let (base, per) = split_period(raw);
+                        let count = base
+                            .parse::<u32>()
+                            .map_err(|_| CommandLineError::InvalidArg(2))?;
+                        if count > 0 && per == GoalPeriod::Daily {
+                            return Err(CommandLineError::InvalidArg(2));
+                        }
+                        (count > 0).then_some(Freezes { count, per })
+                    }
+                    None => None,
+                };
+                Ok(Command::Freeze(args[0].to_string(), freezes))
+            }
+            "pause" => {
+                if args.len() < 2 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 2));
+                }
+                // `pause <habit|all> holidays.ics` imports a calendar's events
+                if args[1].to_lowercase().ends_with(".ics") {
+                    return Ok(Command::ImportPauses(
+                        args[0].to_string(),
+                        args[1..].join(" "),
+                    ));
+                }
+                if args.len() < 3 {
+                    return Err(CommandLineError::NotEnoughArgs(first, 3));
+                }
+                let from = parse_date(&args[1]).ok_or(CommandLineError::InvalidArg(2))?;
+                let to = parse_date(&args[2])
+                    .filter(|&to| to >= from)
+                    .ok_or(CommandLineError::InvalidArg(3))?;
+                Ok(Command::Pause(args[0].to_string(), Pause::new(from, to)))
+            }
+            "unpause" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                let span = match args.get(1) {
+                    Some(raw) => {
+                        if args.len() < 3 {
+                            return Err(CommandLineError::NotEnoughArgs(first, 3));
+                        }
+                        let from = parse_date(raw).ok_or(CommandLineError::InvalidArg(2))?;
+                        let to = parse_date(&args[2])
+                            .filter(|&to| to >= from)
+                            .ok_or(CommandLineError::InvalidArg(3))?;
+                        Some(Pause::new(from, to))
+                    }
+                    None => None,
+                };
+                Ok(Command::Unpause(args[0].to_string(), span))
+            }
             "h" | "?" | "help" => {
                 if args.is_empty() {
                     return Ok(Command::Help(None));
@@ -311,7 +746,19 @@ impl FromStr for Command {
             "q" | "quit" => Ok(Command::Quit),
             "w" | "write" => Ok(Command::Write),
             "archive" => Ok(Command::Archive),
-            "dashboard" | "dash" => Ok(Command::Dashboard),
+            "dashboard" | "dash" => Ok(Command::Dashboard(args.first().cloned())),
+            "retire" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                Ok(Command::Retire(args[0].to_string()))
+            }
+            "unretire" => {
+                if args.is_empty() {
+                    return Err(CommandLineError::NotEnoughArgs(first, 1));
+                }
+                Ok(Command::Unretire(args[0].to_string()))
+            }
             "" => Ok(Command::Blank),
             s => Err(CommandLineError::InvalidCommand(s.into())),
         }
@@ -361,7 +808,13 @@ mod tests {
     fn add_defaults_to_a_daily_goal() {
         assert_eq!(
             "add gym 3".parse::<Command>().unwrap(),
-            Command::Add("gym".into(), Some(GoalKind::Count(3)), GoalPeriod::Daily)
+            Command::Add(
+                "gym".into(),
+                Some(GoalKind::Count(3)),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
         );
     }
 
@@ -370,7 +823,13 @@ mod tests {
         for token in ["3/week", "3/weekly", "3/w"] {
             assert_eq!(
                 format!("add gym {token}").parse::<Command>().unwrap(),
-                Command::Add("gym".into(), Some(GoalKind::Count(3)), GoalPeriod::Weekly),
+                Command::Add(
+                    "gym".into(),
+                    Some(GoalKind::Count(3)),
+                    GoalPeriod::Weekly,
+                    None,
+                    None
+                ),
                 "token `{token}` should parse as a weekly count goal",
             );
         }
@@ -382,11 +841,23 @@ mod tests {
         // daily `1` which is a yes/no Bit habit.
         assert_eq!(
             "add floss 1/week".parse::<Command>().unwrap(),
-            Command::Add("floss".into(), Some(GoalKind::Count(1)), GoalPeriod::Weekly)
+            Command::Add(
+                "floss".into(),
+                Some(GoalKind::Count(1)),
+                GoalPeriod::Weekly,
+                None,
+                None
+            )
         );
         assert_eq!(
             "add floss 1".parse::<Command>().unwrap(),
-            Command::Add("floss".into(), Some(GoalKind::Bit), GoalPeriod::Daily)
+            Command::Add(
+                "floss".into(),
+                Some(GoalKind::Bit),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
         );
     }
 
@@ -397,8 +868,515 @@ mod tests {
             Command::Add(
                 "run".into(),
                 Some(GoalKind::Float(105, 1)),
-                GoalPeriod::Weekly
+                GoalPeriod::Weekly,
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn add_parses_a_limit_as_an_addiction() {
+        assert_eq!(
+            "add coffee <3".parse::<Command>().unwrap(),
+            Command::Add(
+                "coffee".into(),
+                Some(GoalKind::Addiction(3)),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn weekly_limits_are_rejected() {
+        assert!(matches!(
+            "add coffee <3/week".parse::<Command>(),
+            Err(CommandLineError::InvalidGoal(_))
+        ));
+        assert!(matches!(
+            "add mood rating:1-5/month".parse::<Command>(),
+            Err(CommandLineError::InvalidGoal(_))
+        ));
+    }
+
+    #[test]
+    fn add_parses_monthly_goal_suffix() {
+        for token in ["4/month", "4/monthly", "4/m"] {
+            assert_eq!(
+                format!("add runs {token}").parse::<Command>().unwrap(),
+                Command::Add(
+                    "runs".into(),
+                    Some(GoalKind::Count(4)),
+                    GoalPeriod::Monthly,
+                    None,
+                    None
+                ),
+                "token `{token}` should parse as a monthly count goal",
+            );
+        }
+        assert_eq!(
+            "add haircut 1/month".parse::<Command>().unwrap(),
+            Command::Add(
+                "haircut".into(),
+                Some(GoalKind::Count(1)),
+                GoalPeriod::Monthly,
+                None,
+                None
+            )
+        );
+        assert_eq!(
+            "add budget 2h/m".parse::<Command>().unwrap(),
+            Command::Add(
+                "budget".into(),
+                Some(GoalKind::Duration(120)),
+                GoalPeriod::Monthly,
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn commands_tab_complete() {
+        assert_eq!(get_command_completion("se").as_deref(), Some("set"));
+        assert_eq!(get_command_completion("sc").as_deref(), Some("schedule"));
+        assert_eq!(get_command_completion("st").as_deref(), Some("step"));
+        assert_eq!(get_command_completion("unp").as_deref(), Some("unpause"));
+    }
+
+    #[test]
+    fn parse_minutes_understands_common_spellings() {
+        assert_eq!(parse_minutes("45m"), Some(45));
+        assert_eq!(parse_minutes("45min"), Some(45));
+        assert_eq!(parse_minutes("1h"), Some(60));
+        assert_eq!(parse_minutes("1h30"), Some(90));
+        assert_eq!(parse_minutes("1h05m"), Some(65));
+        assert_eq!(parse_minutes("1:30"), Some(90));
+        assert_eq!(parse_minutes("30"), None, "a bare number is a count");
+        assert_eq!(parse_minutes("1h75"), None);
+        assert_eq!(parse_minutes("xm"), None);
+        assert_eq!(parse_minutes("m"), None);
+        assert_eq!(parse_minutes("min"), None);
+        assert_eq!(parse_minutes("1:"), None);
+        assert_eq!(parse_minutes("4294967295h"), None, "too many minutes");
+    }
+
+    #[test]
+    fn add_parses_duration_goals() {
+        assert_eq!(
+            "add read 30m".parse::<Command>().unwrap(),
+            Command::Add(
+                "read".into(),
+                Some(GoalKind::Duration(30)),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
+        );
+        assert_eq!(
+            "add read 1h30/week".parse::<Command>().unwrap(),
+            Command::Add(
+                "read".into(),
+                Some(GoalKind::Duration(90)),
+                GoalPeriod::Weekly,
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn add_parses_cycle_states() {
+        assert_eq!(
+            "add sleep cycle:bad,ok*,good*".parse::<Command>().unwrap(),
+            Command::Add(
+                "sleep".into(),
+                Some(GoalKind::Cycle(vec![
+                    ("bad".into(), false),
+                    ("ok".into(), true),
+                    ("good".into(), true),
+                ])),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn cycle_without_marked_successes_succeeds_on_the_last_state() {
+        assert_eq!(
+            "cycle:✗,~,✓".parse::<GoalKind>().unwrap(),
+            GoalKind::Cycle(vec![
+                ("✗".into(), false),
+                ("~".into(), false),
+                ("✓".into(), true),
+            ])
+        );
+    }
+
+    #[test]
+    fn malformed_cycles_are_rejected() {
+        for token in ["cycle:", "cycle:one", "cycle:a,,b", "cycle:a,b,a"] {
+            assert!(
+                matches!(
+                    token.parse::<GoalKind>(),
+                    Err(CommandLineError::InvalidGoal(_))
+                ),
+                "`{token}` should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn add_parses_rating_scales() {
+        assert_eq!(
+            "add mood rating:1-5".parse::<Command>().unwrap(),
+            Command::Add(
+                "mood".into(),
+                Some(GoalKind::Rating(1, 5)),
+                GoalPeriod::Daily,
+                None,
+                None
+            )
+        );
+        assert_eq!(
+            "rating:10".parse::<GoalKind>().unwrap(),
+            GoalKind::Rating(1, 10)
+        );
+        for token in ["rating:5-1", "rating:3-3", "rating:x"] {
+            assert!(token.parse::<GoalKind>().is_err(), "`{token}` should fail");
+        }
+    }
+
+    #[test]
+    fn note_joins_multi_word_text() {
+        assert_eq!(
+            "note run ran 5k in the rain".parse::<Command>().unwrap(),
+            Command::Note("run".into(), "ran 5k in the rain".into())
+        );
+        assert_eq!(
+            "note run".parse::<Command>().unwrap(),
+            Command::Note("run".into(), String::new())
+        );
+        assert!(matches!(
+            "note".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(_, 1))
+        ));
+    }
+
+    #[test]
+    fn set_takes_a_value_and_an_optional_date() {
+        assert_eq!(
+            "set run 8.5".parse::<Command>().unwrap(),
+            Command::Set("run".into(), "8.5".into(), None)
+        );
+        assert_eq!(
+            "set run 8.5 2024-01-03".parse::<Command>().unwrap(),
+            Command::Set(
+                "run".into(),
+                "8.5".into(),
+                NaiveDate::from_ymd_opt(2024, 1, 3)
+            )
+        );
+        assert!(matches!(
+            "set run 8.5 someday".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(3))
+        ));
+        assert!(matches!(
+            "set run".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(_, 2))
+        ));
+    }
+
+    #[test]
+    fn goal_parses_like_add() {
+        assert_eq!(
+            "goal read 3".parse::<Command>().unwrap(),
+            Command::Goal("read".into(), GoalKind::Count(3), GoalPeriod::Daily)
+        );
+        assert_eq!(
+            "goal gym 1/week".parse::<Command>().unwrap(),
+            Command::Goal("gym".into(), GoalKind::Count(1), GoalPeriod::Weekly)
+        );
+        assert!(matches!(
+            "goal coffee <2/week".parse::<Command>(),
+            Err(CommandLineError::InvalidGoal(_))
+        ));
+        assert!(matches!(
+            "goal read".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn add_takes_a_step_anywhere_after_the_name() {
+        assert_eq!(
+            "add pushups 100 step:10".parse::<Command>().unwrap(),
+            Command::Add(
+                "pushups".into(),
+                Some(GoalKind::Count(100)),
+                GoalPeriod::Daily,
+                None,
+                Some("10".into())
+            )
+        );
+        assert!(matches!(
+            "add run step: 5.0".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+        assert_eq!(
+            "step run 0.5".parse::<Command>().unwrap(),
+            Command::Step("run".into(), "0.5".into())
+        );
+    }
+
+    #[test]
+    fn add_takes_a_group_anywhere_after_the_name() {
+        assert_eq!(
+            "add gym --group health 3/week".parse::<Command>().unwrap(),
+            Command::Add(
+                "gym".into(),
+                Some(GoalKind::Count(3)),
+                GoalPeriod::Weekly,
+                Some("health".into()),
+                None
+            )
+        );
+        assert!(matches!(
+            "add gym 3 --group".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+        assert_eq!(
+            "group gym".parse::<Command>().unwrap(),
+            Command::Group("gym".into(), None)
+        );
+        assert_eq!(
+            "filter work".parse::<Command>().unwrap(),
+            Command::Filter(Some("work".into()))
+        );
+    }
+
+    #[test]
+    fn move_and_sort_parse_their_arguments() {
+        assert_eq!(
+            "move gym 2".parse::<Command>().unwrap(),
+            Command::Move("gym".into(), 2)
+        );
+        assert!(matches!(
+            "move gym 0".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+        assert_eq!(
+            "sort streak".parse::<Command>().unwrap(),
+            Command::Sort(Some(SortKey::Streak), false)
+        );
+        assert_eq!(
+            "sort name keep".parse::<Command>().unwrap(),
+            Command::Sort(Some(SortKey::Name), true)
+        );
+        assert_eq!(
+            "sort none".parse::<Command>().unwrap(),
+            Command::Sort(None, false)
+        );
+        assert!(matches!(
+            "sort size".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(1))
+        ));
+    }
+
+    #[test]
+    fn dashboard_takes_an_optional_habit() {
+        assert_eq!("dash".parse::<Command>().unwrap(), Command::Dashboard(None));
+        assert_eq!(
+            "dashboard read".parse::<Command>().unwrap(),
+            Command::Dashboard(Some("read".into()))
+        );
+        assert_eq!(
+            "retire read".parse::<Command>().unwrap(),
+            Command::Retire("read".into())
+        );
+    }
+
+    #[test]
+    fn rename_takes_old_and_new_names() {
+        assert_eq!(
+            "rename gym lifting".parse::<Command>().unwrap(),
+            Command::Rename("gym".into(), "lifting".into())
+        );
+        assert!(matches!(
+            "rename gym".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn convert_takes_a_goal_expression() {
+        assert_eq!(
+            "convert run 2.5/week".parse::<Command>().unwrap(),
+            Command::Convert("run".into(), GoalKind::Float(25, 1), GoalPeriod::Weekly)
+        );
+        assert!(matches!(
+            "convert run".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn freeze_parses_an_allowance_per_period() {
+        assert_eq!(
+            "freeze gym 2/week".parse::<Command>().unwrap(),
+            Command::Freeze(
+                "gym".into(),
+                Some(Freezes {
+                    count: 2,
+                    per: GoalPeriod::Weekly
+                })
+            )
+        );
+        assert_eq!(
+            "freeze read 1/month".parse::<Command>().unwrap(),
+            Command::Freeze(
+                "read".into(),
+                Some(Freezes {
+                    count: 1,
+                    per: GoalPeriod::Monthly
+                })
             )
         );
+        assert_eq!(
+            "freeze read 0".parse::<Command>().unwrap(),
+            Command::Freeze("read".into(), None)
+        );
+        assert_eq!(
+            "freeze read".parse::<Command>().unwrap(),
+            Command::Freeze("read".into(), None)
+        );
+        assert!(matches!(
+            "freeze read 2".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+    }
+
+    #[test]
+    fn pause_parses_a_span_or_a_calendar_file() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 8, day).unwrap();
+        assert_eq!(
+            "pause all 2024-08-01 2024-08-14"
+                .parse::<Command>()
+                .unwrap(),
+            Command::Pause("all".into(), Pause::new(date(1), date(14)))
+        );
+        assert_eq!(
+            "pause gym holidays.ics".parse::<Command>().unwrap(),
+            Command::ImportPauses("gym".into(), "holidays.ics".into())
+        );
+        assert!(matches!(
+            "pause gym 2024-08-14 2024-08-01".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(3))
+        ));
+        assert!(matches!(
+            "pause gym 2024-08-14".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn unpause_takes_an_optional_span() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 8, day).unwrap();
+        assert_eq!(
+            "unpause gym 2024-08-01 2024-08-03"
+                .parse::<Command>()
+                .unwrap(),
+            Command::Unpause("gym".into(), Some(Pause::new(date(1), date(3))))
+        );
+        assert_eq!(
+            "unpause all".parse::<Command>().unwrap(),
+            Command::Unpause("all".into(), None)
+        );
+        assert!(matches!(
+            "unpause gym 2024-08-01".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn range_parses_a_start_and_optional_end() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 10, day).unwrap();
+        assert_eq!(
+            "range gym 2024-10-01".parse::<Command>().unwrap(),
+            Command::Range("gym".into(), ActiveRange::starting(date(1)))
+        );
+        assert_eq!(
+            "range gym 2024-10-01 2024-10-31"
+                .parse::<Command>()
+                .unwrap(),
+            Command::Range(
+                "gym".into(),
+                ActiveRange {
+                    start: Some(date(1)),
+                    end: Some(date(31)),
+                }
+            )
+        );
+        assert!(matches!(
+            "range gym 10/01/2024".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+        assert!(matches!(
+            "range gym 2024-10-31 2024-10-01".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(3))
+        ));
+        assert!(matches!(
+            "range gym".parse::<Command>(),
+            Err(CommandLineError::NotEnoughArgs(..))
+        ));
+    }
+
+    #[test]
+    fn schedule_parses_intervals() {
+        for spec in ["every:3", "every 3 days", "Every3d"] {
+            assert_eq!(
+                format!("schedule plants {spec}")
+                    .parse::<Command>()
+                    .unwrap(),
+                Command::Schedule("plants".into(), Schedule::every(3)),
+                "spec was {spec}"
+            );
+        }
+        assert!(matches!(
+            "schedule plants every:0".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+        assert!(matches!(
+            "schedule plants every:3:weeks".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
+    }
+
+    #[test]
+    fn schedule_parses_weekday_lists() {
+        assert_eq!(
+            "schedule gym mon,wed,fri".parse::<Command>().unwrap(),
+            Command::Schedule("gym".into(), Schedule::new(vec![Mon, Wed, Fri]))
+        );
+        assert_eq!(
+            "sched gym Friday monday".parse::<Command>().unwrap(),
+            Command::Schedule("gym".into(), Schedule::new(vec![Mon, Fri]))
+        );
+        assert_eq!(
+            "schedule gym weekdays,sat,sun".parse::<Command>().unwrap(),
+            Command::Schedule("gym".into(), Schedule::every_day())
+        );
+        assert_eq!(
+            "schedule gym".parse::<Command>().unwrap(),
+            Command::Schedule("gym".into(), Schedule::every_day())
+        );
+        assert!(matches!(
+            "schedule gym mon,funday".parse::<Command>(),
+            Err(CommandLineError::InvalidArg(2))
+        ));
     }
 }
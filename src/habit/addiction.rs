@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::command::GoalKind;
+use crate::habit::traits::Habit;
+use crate::habit::{
+    ActiveRange, Freezes, GoalHistory, GoalPeriod, InnerData, Pause, Schedule, Step, TrackEvent,
+};
+
+/// A habit to do *less* of: `goal` is a daily limit rather than a target. A
+/// past day is reached when its count stayed at or under the limit, or has no
+/// entry at all (a clean day); today is only judged once it is over.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Addiction {
+    name: String,
+    #[serde(default)]
+    description: String,
+    stats: HashMap<NaiveDate, u32>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    goal_history: GoalHistory,
+    #[serde(default)]
+    step: Step,
+    goal: u32,
+
+    #[serde(skip)]
+    inner_data: InnerData,
+}
+
+impl Addiction {
+    pub fn new(name: impl AsRef<str>, goal: u32) -> Self {
+        Addiction {
+            name: name.as_ref().to_owned(),
+            description: String::new(),
+            stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            goal_history: GoalHistory::default(),
+            step: Step::default(),
+            goal,
+            inner_data: Default::default(),
+        }
+    }
+
+    /// Whether `date` stayed within the limit. A day is only judged once it
+    /// is over, so today is still open however much has been logged so far.
+    fn is_within_limit(&self, date: NaiveDate, today: NaiveDate) -> bool {
+        if date >= today {
+            return false;
+        }
+        match self.stats.get(&date) {
+            Some(&val) => val <= self.limit_on(date),
+            None => true,
+        }
+    }
+
+    /// The limit in force on `date`; earlier limits apply to earlier days.
+    fn limit_on(&self, date: NaiveDate) -> u32 {
+        self.goal_history.at(date, (self.goal, GoalPeriod::Daily)).0
+    }
+
+    /// Every day up to `today` that stayed within the limit. The span starts
+    /// after the last archived month (whose days are already in
+    /// `archived_reached`), or when the habit was started (its first recorded
+    /// entry for older records) otherwise, and stops at its end.
+    fn reached_dates_before(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        let started = self
+            .range
+            .start
+            .or_else(|| self.stats.keys().min().copied());
+        let start = match self.inner_data.archived_reached.iter().max() {
+            Some(last) => first_of_next_month(*last).max(started.unwrap_or(NaiveDate::MIN)),
+            None => match started {
+                Some(first) => first,
+                None => return Vec::new(),
+            },
+        };
+        let until = self.range.end.map_or(today, |end| end.min(today));
+
+        let mut out = Vec::new();
+        let mut day = start;
+        while day <= until {
+            if self.is_within_limit(day, today) {
+                out.push(day);
+            }
+            day = match day.checked_add_days(Days::new(1)) {
+                Some(d) => d,
+                None => break,
+            };
+        }
+        out
+    }
+}
+
+fn first_of_next_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or(date)
+}
+
+impl Habit for Addiction {
+    type HabitType = u32;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
+    fn kind(&self) -> GoalKind {
+        GoalKind::Addiction(self.goal)
+    }
+    fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
+        self.stats.get(&date)
+    }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(u32::to_string)
+    }
+    fn get_dates(&self) -> Vec<NaiveDate> {
+        self.stats.keys().copied().collect()
+    }
+    fn insert_entry(&mut self, date: NaiveDate, val: Self::HabitType) {
+        *self.stats.entry(date).or_insert(val) = val;
+    }
+    fn reached_goal(&self, date: NaiveDate) -> bool {
+        self.is_within_limit(date, Local::now().date_naive())
+    }
+    fn reached_dates(&self) -> Vec<NaiveDate> {
+        self.reached_dates_before(Local::now().date_naive())
+    }
+    /// An empty day is a clean one; only a day over the limit is missed.
+    fn missed_on(&self, date: NaiveDate) -> bool {
+        self.stats
+            .get(&date)
+            .is_some_and(|&val| val > self.limit_on(date))
+    }
+    /// Nothing is left to do on a day that stays within the limit, so today
+    /// counts as done until it goes over, though it is only reached once over.
+    fn remaining(&self, date: NaiveDate) -> u32 {
+        u32::from(self.missed_on(date))
+    }
+    /// Staying within the limit is a single yes/no target per day, so the
+    /// limit itself is exposed through `kind` rather than as the goal.
+    fn goal(&self) -> u32 {
+        1
+    }
+    fn limit_at(&self, date: NaiveDate) -> Option<u32> {
+        Some(self.limit_on(date))
+    }
+    fn set_goal(
+        &mut self,
+        kind: GoalKind,
+        _period: GoalPeriod,
+        since: NaiveDate,
+    ) -> Result<(), String> {
+        let GoalKind::Addiction(limit) = kind else {
+            return Err(format!("`{}` has a limit, give it as <n", self.name));
+        };
+        self.goal_history
+            .retire((self.goal, GoalPeriod::Daily), since);
+        self.goal = limit;
+        Ok(())
+    }
+    fn step(&self) -> u32 {
+        self.step.0
+    }
+    fn set_step(&mut self, step: u32) -> Result<(), String> {
+        if step == 0 {
+            return Err(format!("`{}` needs a step above zero", self.name));
+        }
+        self.step = Step(step);
+        Ok(())
+    }
+    fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
+        match event {
+            TrackEvent::Increment => {
+                if let Some(val) = self.stats.get_mut(&date) {
+                    *val = val.saturating_add(self.step.0)
+                } else {
+                    self.insert_entry(date, self.step.0);
+                }
+            }
+            TrackEvent::Decrement => {
+                if let Some(val) = self.stats.get_mut(&date) {
+                    if *val > 0 {
+                        *val = val.saturating_sub(self.step.0)
+                    } else {
+                        self.stats.remove(&date);
+                    }
+                }
+            }
+            // zero is a logged clean day, not a missing one
+            TrackEvent::Set(val) => self.insert_entry(date, val),
+        }
+    }
+    fn inner_data_ref(&self) -> &InnerData {
+        &self.inner_data
+    }
+    fn inner_data_mut_ref(&mut self) -> &mut InnerData {
+        &mut self.inner_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn staying_at_or_under_the_limit_is_reached() {
+        let mut h = Addiction::new("coffee", 2);
+        h.insert_entry(d(1), 1);
+        h.insert_entry(d(2), 2);
+        h.insert_entry(d(3), 3);
+        assert!(h.is_within_limit(d(1), d(10)));
+        assert!(
+            h.is_within_limit(d(2), d(10)),
+            "the limit itself is allowed"
+        );
+        assert!(
+            !h.is_within_limit(d(3), d(10)),
+            "over the limit is a failure"
+        );
+    }
+
+    #[test]
+    fn an_empty_past_day_is_a_clean_day() {
+        let h = Addiction::new("coffee", 0);
+        assert!(h.is_within_limit(d(1), d(10)));
+        // today and the future are still open
+        assert!(!h.is_within_limit(d(10), d(10)));
+        assert!(!h.is_within_limit(d(11), d(10)));
+    }
+
+    #[test]
+    fn today_is_open_whatever_was_logged() {
+        let mut h = Addiction::new("coffee", 2);
+        assert!(!h.is_within_limit(d(10), d(10)));
+        h.insert_entry(d(10), 0);
+        assert!(
+            !h.is_within_limit(d(10), d(10)),
+            "a clean start is not a clean day yet"
+        );
+        h.insert_entry(d(10), 2);
+        assert!(!h.is_within_limit(d(10), d(10)));
+        assert!(
+            h.is_within_limit(d(10), d(11)),
+            "judged once the day is over"
+        );
+    }
+
+    #[test]
+    fn only_days_over_the_limit_are_missed() {
+        let mut h = Addiction::new("coffee", 1);
+        h.insert_entry(d(2), 1);
+        h.insert_entry(d(3), 4);
+        assert!(!h.missed_on(d(1)), "a clean day");
+        assert!(!h.missed_on(d(2)));
+        assert!(h.missed_on(d(3)));
+    }
+
+    #[test]
+    fn nothing_remains_of_a_day_within_the_limit() {
+        let mut h = Addiction::new("coffee", 1);
+        let today = Local::now().date_naive();
+        assert_eq!(h.remaining(today), 0, "nothing had yet");
+        h.insert_entry(today, 1);
+        assert_eq!(h.remaining(today), 0);
+        h.insert_entry(today, 2);
+        assert_eq!(h.remaining(today), 1, "over the limit");
+    }
+
+    #[test]
+    fn reached_dates_fill_empty_days_since_the_first_entry() {
+        let mut h = Addiction::new("coffee", 1);
+        h.insert_entry(d(2), 1);
+        h.insert_entry(d(4), 5); // over the limit
+        let got = h.reached_dates_before(d(6));
+        assert_eq!(got, vec![d(2), d(3), d(5)]);
+    }
+
+    #[test]
+    fn reached_dates_resume_after_the_archived_months() {
+        let mut h = Addiction::new("coffee", 1);
+        h.inner_data.archived_reached.insert(d(20));
+        let feb = |day| NaiveDate::from_ymd_opt(2024, 2, day).unwrap();
+        let got = h.reached_dates_before(feb(3));
+        assert_eq!(got, vec![feb(1), feb(2)]);
+    }
+
+    #[test]
+    fn reached_dates_stay_within_the_active_range() {
+        let mut h = Addiction::new("coffee", 1);
+        h.set_range(ActiveRange {
+            start: Some(d(3)),
+            end: Some(d(5)),
+        });
+        assert_eq!(h.reached_dates_before(d(10)), vec![d(3), d(4), d(5)]);
+    }
+
+    #[test]
+    fn limit_survives_a_serde_round_trip() {
+        let h = Addiction::new("coffee", 3);
+        let json = serde_json::to_string(&h).unwrap();
+        let back: Addiction = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.kind(), GoalKind::Addiction(3));
+    }
+}
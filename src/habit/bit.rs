@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::CONFIGURATION;
 use crate::command::GoalKind;
 use crate::habit::traits::Habit;
-use crate::habit::{InnerData, TrackEvent};
+use crate::habit::{ActiveRange, Freezes, InnerData, Pause, Schedule, TrackEvent};
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct CustomBool(bool);
@@ -38,6 +38,18 @@ pub struct Bit {
     #[serde(default)]
     description: String,
     stats: HashMap<NaiveDate, CustomBool>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
     goal: CustomBool,
 
     #[serde(skip)]
@@ -50,6 +62,12 @@ impl Bit {
             name: name.as_ref().to_owned(),
             description: String::new(),
             stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
             goal: CustomBool(true),
             inner_data: Default::default(),
         }
@@ -64,15 +82,59 @@ impl Habit for Bit {
     fn description(&self) -> &str {
         &self.description
     }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
     fn set_description(&mut self, description: String) {
         self.description = description;
     }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
     fn kind(&self) -> GoalKind {
         GoalKind::Bit
     }
     fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
         self.stats.get(&date)
     }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats
+            .get(&date)
+            .map(|b| if b.0 { "yes" } else { "no" }.to_owned())
+    }
     fn get_dates(&self) -> Vec<NaiveDate> {
         self.stats.keys().copied().collect()
     }
@@ -112,6 +174,17 @@ impl Habit for Bit {
                     }
                 }
             }
+            TrackEvent::Set(0) => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(_) => self.insert_entry(date, CustomBool(true)),
+        }
+    }
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        match input.to_lowercase().as_str() {
+            "1" | "y" | "yes" | "done" => Some(1),
+            "0" | "n" | "no" => Some(0),
+            _ => None,
         }
     }
     fn inner_data_ref(&self) -> &InnerData {
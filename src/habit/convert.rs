@@ -0,0 +1,249 @@
+use serde_json::{Map, Value, json};
+
+use crate::command::GoalKind;
+use crate::habit::{GoalPeriod, HabitWrapper};
+
+/// Rebuild `habit` as a `Bit`, `Count` or `Float` habit with the goal `kind`
+/// per `period`, carrying over its entries and everything that does not
+/// depend on its kind (description, notes, schedule, range, pauses, freezes).
+///
+/// Entries and the step are migrated through their stored form: `Float`
+/// values are scaled between precisions, counts and floats become done when
+/// positive, and done becomes 1. Entries of 0 are kept as 0, or not done.
+/// Returns the new habit and how many days could not be carried over exactly,
+/// because they were rounded or collapsed to done.
+pub fn convert(
+    habit: &dyn HabitWrapper,
+    kind: &GoalKind,
+    period: GoalPeriod,
+) -> Result<(Box<dyn HabitWrapper>, usize), String> {
+    let mut json = serde_json::to_value(habit).map_err(|e| e.to_string())?;
+    let obj = json
+        .as_object_mut()
+        .ok_or_else(|| format!("`{}` could not be read", habit.name()))?;
+
+    let from = obj.get("type").and_then(Value::as_str).unwrap_or_default();
+    let from_precision = match from {
+        "Bit" | "Count" => 0,
+        "Float" => obj.get("precision").and_then(Value::as_u64).unwrap_or(0) as u8,
+        other => {
+            return Err(format!(
+                "`{}` is a {} habit, only yes/no, count and number habits can be converted",
+                habit.name(),
+                other.to_lowercase()
+            ));
+        }
+    };
+    let to_precision = match kind {
+        GoalKind::Bit | GoalKind::Count(_) => 0,
+        GoalKind::Float(_, precision) => *precision,
+        _ => return Err("habits can only be converted to a yes/no, count or number goal".into()),
+    };
+
+    // every entry as an integer in `from_precision` decimal places
+    let raw = |value: &Value| -> u64 {
+        match value {
+            Value::Bool(done) => u64::from(*done),
+            Value::Object(o) => o.get("value").and_then(Value::as_u64).unwrap_or(0),
+            v => v.as_u64().unwrap_or(0),
+        }
+    };
+
+    let mut lossy = 0;
+    let mut stats = Map::new();
+    for (date, value) in obj
+        .get("stats")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+    {
+        let value = raw(value);
+        let (converted, exact) = match kind {
+            GoalKind::Bit => (
+                json!(value > 0),
+                value == 0 || value == scale(from_precision),
+            ),
+            _ => {
+                let (scaled, exact) = rescale(value, from_precision, to_precision);
+                // a value too small for the new unit still counts for something
+                let scaled = if value > 0 { scaled.max(1) } else { 0 };
+                match kind {
+                    GoalKind::Float(..) => {
+                        (json!({ "value": scaled, "precision": to_precision }), exact)
+                    }
+                    _ => (json!(scaled), exact),
+                }
+            }
+        };
+        lossy += usize::from(!exact);
+        stats.insert(date.clone(), converted);
+    }
+    obj.insert("stats".into(), Value::Object(stats));
+
+    // earlier goals keep applying to earlier days, in the new unit
+    let history = obj.remove("goal_history").and_then(|h| match h {
+        Value::Array(revisions) if !matches!(kind, GoalKind::Bit) => Some(
+            revisions
+                .into_iter()
+                .map(|mut r| {
+                    let goal = r.get("goal").and_then(Value::as_u64).unwrap_or(0);
+                    r["goal"] = json!(rescale(goal, from_precision, to_precision).0);
+                    r
+                })
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    });
+
+    // the step moves to the new unit too; yes/no habits have none
+    let step = obj
+        .remove("step")
+        .as_ref()
+        .and_then(Value::as_u64)
+        .map(|step| {
+            let (scaled, _) = rescale(step, from_precision, to_precision);
+            scaled.max(1)
+        });
+
+    obj.remove("precision");
+    obj.remove("period");
+    match kind {
+        GoalKind::Bit => {
+            obj.insert("type".into(), json!("Bit"));
+            obj.insert("goal".into(), json!(true));
+        }
+        GoalKind::Count(goal) => {
+            obj.insert("type".into(), json!("Count"));
+            obj.insert("goal".into(), json!(goal));
+        }
+        GoalKind::Float(goal, precision) => {
+            obj.insert("type".into(), json!("Float"));
+            obj.insert(
+                "goal".into(),
+                json!({ "value": goal, "precision": precision }),
+            );
+            obj.insert("precision".into(), json!(precision));
+        }
+        _ => unreachable!("checked above"),
+    }
+    if !matches!(kind, GoalKind::Bit) {
+        obj.insert("period".into(), serde_json::to_value(period).unwrap());
+        if let Some(history) = history {
+            obj.insert("goal_history".into(), Value::Array(history));
+        }
+        if let Some(step) = step {
+            obj.insert("step".into(), json!(step));
+        }
+    }
+
+    let converted = serde_json::from_value(json).map_err(|e| e.to_string())?;
+    Ok((converted, lossy))
+}
+
+fn scale(precision: u8) -> u64 {
+    10u64.pow(precision as u32)
+}
+
+/// `value` with `from` decimal places written with `to` of them, rounded to
+/// the nearest, and whether that was exact.
+fn rescale(value: u64, from: u8, to: u8) -> (u64, bool) {
+    if to >= from {
+        (value * scale(to - from), true)
+    } else {
+        let div = scale(from - to);
+        ((value + div / 2) / div, value.is_multiple_of(div))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::habit::Duration;
+    use crate::habit::{Bit, Count, Float, Habit};
+    use chrono::NaiveDate;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn count_to_float_scales_every_entry() {
+        let mut h = Count::new("run", 3);
+        Habit::set_description(&mut h, "km".into());
+        h.insert_entry(d(1), 2);
+        h.insert_entry(d(2), 5);
+
+        let (h, lossy) = convert(&h, &GoalKind::Float(25, 1), GoalPeriod::Daily).unwrap();
+        assert_eq!(lossy, 0);
+        assert_eq!(h.kind(), GoalKind::Float(25, 1));
+        assert_eq!(h.description(), "km");
+        assert_eq!(h.remaining(d(1)), 5, "2.0 of 2.5");
+        assert_eq!(h.remaining(d(2)), 0);
+    }
+
+    #[test]
+    fn float_to_count_rounds_and_reports_it() {
+        let h: Float = serde_json::from_value(json!({
+            "name": "run", "goal": { "value": 25, "precision": 1 }, "precision": 1,
+            "stats": {
+                "2024-01-01": { "value": 30, "precision": 1 },
+                "2024-01-02": { "value": 26, "precision": 1 },
+            },
+        }))
+        .unwrap();
+
+        let (h, lossy) = convert(&h, &GoalKind::Count(3), GoalPeriod::Weekly).unwrap();
+        assert_eq!(lossy, 1);
+        assert_eq!(h.kind(), GoalKind::Count(3));
+        assert_eq!(h.period(), GoalPeriod::Weekly);
+        assert_eq!(h.remaining(d(1)), 0, "3 + 3 this week");
+    }
+
+    #[test]
+    fn bit_and_count_convert_both_ways() {
+        let mut h = Bit::new("floss");
+        h.insert_entry(d(1), true.into());
+        let (h, lossy) = convert(&h, &GoalKind::Count(2), GoalPeriod::Daily).unwrap();
+        assert_eq!(lossy, 0);
+        assert_eq!(h.remaining(d(1)), 1);
+
+        let mut c = Count::new("floss", 2);
+        c.insert_entry(d(1), 1);
+        c.insert_entry(d(2), 3);
+        let (h, lossy) = convert(&c, &GoalKind::Bit, GoalPeriod::Daily).unwrap();
+        assert_eq!(lossy, 1, "3 collapses to done");
+        assert_eq!(h.kind(), GoalKind::Bit);
+        assert_eq!(h.remaining(d(2)), 0);
+    }
+
+    #[test]
+    fn zero_entries_are_carried_over() {
+        let mut c = Count::new("pushups", 2);
+        c.insert_entry(d(1), 0);
+        let (h, lossy) = convert(&c, &GoalKind::Float(15, 1), GoalPeriod::Daily).unwrap();
+        assert_eq!(lossy, 0);
+        assert_eq!(h.get_dates(), vec![d(1)]);
+        assert_eq!(h.entry_value(d(1)).as_deref(), Some("0"));
+
+        let mut b = Bit::new("floss");
+        b.insert_entry(d(1), false.into());
+        let (h, _) = convert(&b, &GoalKind::Count(2), GoalPeriod::Daily).unwrap();
+        assert_eq!(h.entry_value(d(1)).as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn the_step_follows_the_new_unit() {
+        let mut h = Count::new("run", 3);
+        Habit::set_step(&mut h, 2).unwrap();
+        let (h, _) = convert(&h, &GoalKind::Float(25, 1), GoalPeriod::Daily).unwrap();
+        assert_eq!(h.step(), 20, "2 km in tenths");
+    }
+
+    #[test]
+    fn other_kinds_are_not_converted() {
+        let h = Duration::new("read", 30);
+        assert!(convert(&h, &GoalKind::Count(2), GoalPeriod::Daily).is_err());
+        let c = Count::new("read", 3);
+        assert!(convert(&c, &GoalKind::Duration(30), GoalPeriod::Daily).is_err());
+    }
+}
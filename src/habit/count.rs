@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
-use chrono::{Days, NaiveDate};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::command::GoalKind;
 use crate::habit::traits::Habit;
-use crate::habit::{GoalPeriod, InnerData, TrackEvent};
-use crate::utils::week_bounds;
+use crate::habit::{
+    ActiveRange, Freezes, GoalHistory, GoalPeriod, InnerData, Pause, Schedule, Step, TrackEvent,
+};
+use crate::utils::period_total;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Count {
@@ -14,6 +16,22 @@ pub struct Count {
     #[serde(default)]
     description: String,
     stats: HashMap<NaiveDate, u32>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    goal_history: GoalHistory,
+    #[serde(default)]
+    step: Step,
     goal: u32,
     #[serde(default)]
     period: GoalPeriod,
@@ -28,6 +46,14 @@ impl Count {
             name: name.as_ref().to_owned(),
             description: String::new(),
             stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            goal_history: GoalHistory::default(),
+            step: Step::default(),
             goal,
             period: GoalPeriod::Daily,
             inner_data: Default::default(),
@@ -39,19 +65,16 @@ impl Count {
         self
     }
 
-    /// Sum of every entry in the Mon–Sun week containing `date`.
-    fn week_total(&self, date: NaiveDate) -> u32 {
-        let (monday, sunday) = week_bounds(date);
-        let mut day = monday;
-        let mut total = 0;
-        while day <= sunday {
-            total += self.stats.get(&day).copied().unwrap_or(0);
-            day = match day.checked_add_days(Days::new(1)) {
-                Some(d) => d,
-                None => break,
-            };
-        }
-        total
+    /// The goal and period in force on `date`.
+    fn goal_on(&self, date: NaiveDate) -> (u32, GoalPeriod) {
+        self.goal_history.at(date, (self.goal, self.period))
+    }
+
+    /// Sum of every entry in the week or month containing `date`.
+    fn period_total(&self, date: NaiveDate, period: GoalPeriod) -> u32 {
+        period_total(date, period, |day| {
+            self.stats.get(&day).copied().unwrap_or(0)
+        })
     }
 }
 
@@ -64,15 +87,57 @@ impl Habit for Count {
     fn description(&self) -> &str {
         &self.description
     }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
     fn set_description(&mut self, description: String) {
         self.description = description;
     }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
     fn kind(&self) -> GoalKind {
         GoalKind::Count(self.goal)
     }
     fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
         self.stats.get(&date)
     }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(|&v| self.format_value(v))
+    }
     fn get_dates(&self) -> Vec<NaiveDate> {
         self.stats.keys().copied().collect()
     }
@@ -80,49 +145,81 @@ impl Habit for Count {
         *self.stats.entry(date).or_insert(val) = val;
     }
     fn reached_goal(&self, date: NaiveDate) -> bool {
-        match self.period {
-            GoalPeriod::Daily => self.stats.get(&date).is_some_and(|val| val >= &self.goal),
-            GoalPeriod::Weekly => self.week_total(date) >= self.goal,
+        let (goal, period) = self.goal_on(date);
+        match period {
+            GoalPeriod::Daily => self.stats.get(&date).is_some_and(|&val| val >= goal),
+            GoalPeriod::Weekly | GoalPeriod::Monthly => self.period_total(date, period) >= goal,
         }
     }
     fn remaining(&self, date: NaiveDate) -> u32 {
-        match self.period {
-            GoalPeriod::Daily => {
-                if self.reached_goal(date) {
-                    0
-                } else if let Some(val) = self.stats.get(&date) {
-                    self.goal - val
-                } else {
-                    self.goal
-                }
+        let (goal, period) = self.goal_on(date);
+        match period {
+            GoalPeriod::Daily => goal.saturating_sub(self.stats.get(&date).copied().unwrap_or(0)),
+            GoalPeriod::Weekly | GoalPeriod::Monthly => {
+                goal.saturating_sub(self.period_total(date, period))
             }
-            GoalPeriod::Weekly => self.goal.saturating_sub(self.week_total(date)),
         }
     }
     fn goal(&self) -> u32 {
         self.goal
     }
+    fn goal_at(&self, date: NaiveDate) -> u32 {
+        self.goal_on(date).0
+    }
+    fn period_at(&self, date: NaiveDate) -> GoalPeriod {
+        self.goal_on(date).1
+    }
+    fn set_goal(
+        &mut self,
+        kind: GoalKind,
+        period: GoalPeriod,
+        since: NaiveDate,
+    ) -> Result<(), String> {
+        let goal = match kind {
+            GoalKind::Count(n) => n,
+            GoalKind::Bit => 1,
+            _ => return Err(format!("`{}` counts, so its goal is a number", self.name)),
+        };
+        self.goal_history.retire((self.goal, self.period), since);
+        self.goal = goal;
+        self.period = period;
+        Ok(())
+    }
     fn period(&self) -> GoalPeriod {
         self.period
     }
+    fn step(&self) -> u32 {
+        self.step.0
+    }
+    fn set_step(&mut self, step: u32) -> Result<(), String> {
+        if step == 0 {
+            return Err(format!("`{}` needs a step above zero", self.name));
+        }
+        self.step = Step(step);
+        Ok(())
+    }
     fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
         match event {
             TrackEvent::Increment => {
                 if let Some(val) = self.stats.get_mut(&date) {
-                    *val += 1
+                    *val = val.saturating_add(self.step.0)
                 } else {
-                    self.insert_entry(date, 1);
+                    self.insert_entry(date, self.step.0);
                 }
             }
             TrackEvent::Decrement => {
                 if let Some(val) = self.stats.get_mut(&date) {
                     if *val > 0 {
-                        *val -= 1
+                        *val = val.saturating_sub(self.step.0)
                     } else {
                         self.stats.remove(&date);
                     }
                 }
             }
+            TrackEvent::Set(0) => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(val) => self.insert_entry(date, val),
         }
     }
     fn inner_data_ref(&self) -> &InnerData {
@@ -142,6 +239,50 @@ mod tests {
         NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
     }
 
+    #[test]
+    fn goal_change_applies_from_its_date_on() {
+        let mut h = Count::new("pushups", 2);
+        h.insert_entry(d(5), 2);
+        h.insert_entry(d(12), 2);
+        h.set_goal(GoalKind::Count(3), GoalPeriod::Daily, d(10))
+            .unwrap();
+
+        assert!(h.reached_goal(d(5)), "judged against the goal of the time");
+        assert!(!h.reached_goal(d(12)));
+        assert_eq!(h.remaining(d(12)), 1);
+        assert_eq!(h.goal_at(d(9)), 2);
+        assert_eq!(h.goal_at(d(10)), 3);
+    }
+
+    #[test]
+    fn goal_change_can_switch_the_period() {
+        let mut h = Count::new("gym", 1);
+        h.insert_entry(d(1), 1);
+        h.insert_entry(d(8), 1);
+        h.set_goal(GoalKind::Count(2), GoalPeriod::Weekly, d(8))
+            .unwrap();
+
+        assert!(h.reached_goal(d(1)), "the first week was still daily");
+        assert!(!h.reached_goal(d(8)));
+        h.insert_entry(d(10), 1);
+        assert!(h.reached_goal(d(8)));
+    }
+
+    #[test]
+    fn goal_replaced_twice_on_one_day_keeps_only_the_first() {
+        let mut h = Count::new("pushups", 2);
+        h.set_goal(GoalKind::Count(3), GoalPeriod::Daily, d(10))
+            .unwrap();
+        h.set_goal(GoalKind::Count(4), GoalPeriod::Daily, d(10))
+            .unwrap();
+        assert_eq!(h.goal_at(d(9)), 2);
+        assert_eq!(h.goal_at(d(10)), 4);
+        assert!(
+            h.set_goal(GoalKind::Duration(30), GoalPeriod::Daily, d(10))
+                .is_err()
+        );
+    }
+
     #[test]
     fn weekly_goal_aggregates_the_whole_week() {
         let mut h = Count::new("gym", 3).with_period(GoalPeriod::Weekly);
@@ -168,6 +309,19 @@ mod tests {
         assert_eq!(h.remaining(d(10)), 2);
     }
 
+    #[test]
+    fn monthly_goal_aggregates_the_calendar_month() {
+        let mut h = Count::new("long run", 4).with_period(GoalPeriod::Monthly);
+        h.insert_entry(d(1), 1);
+        h.insert_entry(d(15), 2);
+        assert_eq!(h.remaining(d(31)), 1);
+        h.insert_entry(d(31), 1);
+        assert!(h.reached_goal(d(10)));
+        let feb = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        assert!(!h.reached_goal(feb), "February starts from scratch");
+        assert_eq!(h.remaining(feb), 4);
+    }
+
     #[test]
     fn period_survives_a_serde_round_trip() {
         let h = Count::new("gym", 3).with_period(GoalPeriod::Weekly);
@@ -185,6 +339,161 @@ mod tests {
         assert_eq!(h.period, GoalPeriod::Daily);
     }
 
+    #[test]
+    fn notes_are_optional_in_the_record() {
+        let json = r#"{"name":"read","description":"","stats":{},"goal":2}"#;
+        let mut h: Count = serde_json::from_str(json).unwrap();
+        assert!(h.notes().is_empty());
+
+        h.set_note(d(1), "ran 5k in the rain".into());
+        let back: Count = serde_json::from_str(&serde_json::to_string(&h).unwrap()).unwrap();
+        assert_eq!(back.notes().get(&d(1)).unwrap(), "ran 5k in the rain");
+
+        h.set_note(d(1), String::new());
+        assert!(h.notes().is_empty(), "an empty note clears the day");
+    }
+
+    #[test]
+    fn completion_rate_counts_from_the_start_of_the_range() {
+        let mut h = Count::new("read", 1);
+        h.insert_entry(d(3), 1);
+        h.insert_entry(d(4), 1);
+        assert_eq!(Habit::stats_between(&h, None, d(4)).completion_rate, 100);
+        h.set_range(ActiveRange::starting(d(1)));
+        assert_eq!(
+            Habit::stats_between(&h, None, d(4)).completion_rate,
+            50,
+            "days 1 and 2 were missed"
+        );
+        h.set_range(ActiveRange::starting(d(4)));
+        assert_eq!(
+            Habit::stats_between(&h, None, d(4)).total,
+            1,
+            "day 3 is before the range"
+        );
+    }
+
+    #[test]
+    fn interval_habit_is_due_n_days_after_its_last_completion() {
+        let mut h = Count::new("plants", 1);
+        h.set_schedule(Schedule::every(3));
+        assert!(h.is_due(d(1)), "never done, so due straight away");
+        assert_eq!(h.days_until_due(d(1)), Some(0));
+
+        h.insert_entry(d(2), 1);
+        assert!(!h.is_due(d(3)) && !h.is_due(d(4)));
+        assert!(h.is_due(d(5)));
+        assert_eq!(h.days_until_due(d(2)), Some(3), "done today");
+        assert_eq!(h.days_until_due(d(4)), Some(1));
+        assert_eq!(h.days_until_due(d(7)), Some(-2), "overdue");
+    }
+
+    #[test]
+    fn interval_schedule_survives_a_serde_round_trip() {
+        let mut h = Count::new("filter", 1);
+        h.set_schedule(Schedule::every(14));
+        let json = serde_json::to_string(&h).unwrap();
+        assert!(
+            json.contains("\"schedule\":{\"every\":14}"),
+            "json was {json}"
+        );
+        let back: Count = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.schedule().interval(), Some(14));
+        assert_eq!(Count::new("x", 1).days_until_due(d(1)), None);
+    }
+
+    #[test]
+    fn pauses_push_back_an_interval_habit() {
+        let mut h = Count::new("plants", 1);
+        h.set_schedule(Schedule::every(3));
+        h.insert_entry(d(1), 1);
+        h.add_pause(Pause::new(d(2), d(3)));
+        h.add_pause(Pause::new(d(2), d(3)));
+        assert_eq!(h.pauses().len(), 1, "the same pause is recorded once");
+        assert!(!h.is_due(d(3)), "paused days are never due");
+        assert!(!h.is_due(d(4)), "only one unpaused day has passed");
+        assert!(h.is_due(d(6)));
+        assert_eq!(h.days_until_due(d(4)), Some(2));
+    }
+
+    #[test]
+    fn due_dates_agree_with_is_due_day_by_day() {
+        let mut h = Count::new("plants", 1);
+        h.set_schedule(Schedule::every(3));
+        for day in [2, 5, 6, 14] {
+            h.insert_entry(d(day), 1);
+        }
+        h.inner_data_mut_ref().archived_reached.insert(d(1));
+        h.add_pause(Pause::new(d(8), d(10)));
+        for from in [1, 4, 9] {
+            let due = h.due_dates(d(from), d(20));
+            for day in from..=20 {
+                assert_eq!(
+                    due.contains(&d(day)),
+                    h.is_due(d(day)),
+                    "day {day} from {from}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overlapping_pauses_take_each_day_off_once() {
+        let mut h = Count::new("plants", 1);
+        h.set_schedule(Schedule::every(3));
+        h.insert_entry(d(1), 1);
+        h.add_pause(Pause::new(d(2), d(4)));
+        h.add_pause(Pause::new(d(3), d(5)));
+        h.add_pause(Pause::new(d(3), d(3)));
+        assert_eq!(h.unpaused_days(d(1), d(6)), 1);
+        assert!(!h.is_due(d(7)));
+        assert!(h.is_due(d(8)));
+    }
+
+    #[test]
+    fn unpausing_a_span_splits_the_pauses_over_it() {
+        let mut h = Count::new("gym", 1);
+        h.add_pause(Pause::new(d(1), d(10)));
+        h.add_pause(Pause::new(d(20), d(21)));
+        h.remove_pause(Pause::new(d(4), d(5)));
+        assert_eq!(
+            h.pauses(),
+            [
+                Pause::new(d(1), d(3)),
+                Pause::new(d(6), d(10)),
+                Pause::new(d(20), d(21))
+            ]
+        );
+        h.remove_pause(Pause::new(d(1), d(20)));
+        assert_eq!(h.pauses(), [Pause::new(d(21), d(21))]);
+    }
+
+    #[test]
+    fn legacy_records_start_at_their_first_entry() {
+        let json = r#"{"name":"read","description":"","stats":{"2024-01-03":1},"goal":2}"#;
+        let h: Count = serde_json::from_str(json).unwrap();
+        assert_eq!(h.range(), ActiveRange::default());
+        assert!(!h.is_due(d(2)));
+        assert!(h.is_due(d(3)));
+    }
+
+    #[test]
+    fn active_range_is_stored_inline() {
+        let mut h = Count::new("read", 2);
+        h.set_range(ActiveRange {
+            start: Some(d(1)),
+            end: Some(d(7)),
+        });
+        let json = serde_json::to_string(&h).unwrap();
+        assert!(
+            json.contains("\"start\":\"2024-01-01\",\"end\":\"2024-01-07\""),
+            "json was {json}"
+        );
+        let back: Count = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.range(), h.range());
+        assert!(!back.is_due(d(8)));
+    }
+
     #[test]
     fn daily_goal_is_unchanged_by_the_period_field() {
         let mut h = Count::new("water", 3); // defaults to daily
@@ -193,4 +502,32 @@ mod tests {
         assert!(!h.reached_goal(d(2)));
         assert_eq!(h.remaining(d(2)), 3);
     }
+
+    #[test]
+    fn increment_and_decrement_move_by_the_step() {
+        let mut h = Count::new("pushups", 100);
+        h.set_step(10).unwrap();
+        h.modify(d(1), TrackEvent::Increment);
+        h.modify(d(1), TrackEvent::Increment);
+        assert_eq!(h.get_by_date(d(1)), Some(&20));
+        h.modify(d(1), TrackEvent::Decrement);
+        assert_eq!(h.get_by_date(d(1)), Some(&10));
+        assert!(h.set_step(0).is_err());
+    }
+
+    #[test]
+    fn increment_stops_at_the_largest_count() {
+        let mut h = Count::new("pushups", 100);
+        h.set_step(10).unwrap();
+        h.insert_entry(d(1), u32::MAX - 5);
+        h.modify(d(1), TrackEvent::Increment);
+        assert_eq!(h.get_by_date(d(1)), Some(&u32::MAX));
+    }
+
+    #[test]
+    fn records_without_a_step_step_by_one() {
+        let h: Count =
+            serde_json::from_str(r#"{"name": "pushups", "stats": {}, "goal": 10}"#).unwrap();
+        assert_eq!(h.step(), 1);
+    }
 }
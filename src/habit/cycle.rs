@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::command::GoalKind;
+use crate::habit::traits::Habit;
+use crate::habit::{ActiveRange, Freezes, InnerData, Pause, Schedule, TrackEvent};
+
+/// The state a `Cycle` habit was left in on a day, stored by its label so the
+/// record stays readable. Drawn as at most three characters.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CycleState(String);
+
+impl fmt::Display for CycleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let glyph: String = self.0.chars().take(3).collect();
+        f.pad(&glyph)
+    }
+}
+
+/// A habit with `n` named states that `Increment`/`Decrement` step through.
+/// Only the states listed in `successes` reach the day's goal.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cycle {
+    name: String,
+    #[serde(default)]
+    description: String,
+    stats: HashMap<NaiveDate, CycleState>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    states: Vec<String>,
+    successes: Vec<String>,
+
+    #[serde(skip)]
+    inner_data: InnerData,
+}
+
+impl Cycle {
+    /// `states` pairs each state's label with whether it counts as a success.
+    pub fn new(name: impl AsRef<str>, states: Vec<(String, bool)>) -> Self {
+        Cycle {
+            name: name.as_ref().to_owned(),
+            description: String::new(),
+            stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            successes: states
+                .iter()
+                .filter(|(_, success)| *success)
+                .map(|(label, _)| label.clone())
+                .collect(),
+            states: states.into_iter().map(|(label, _)| label).collect(),
+            inner_data: Default::default(),
+        }
+    }
+
+    fn position(&self, state: &CycleState) -> usize {
+        self.states.iter().position(|s| *s == state.0).unwrap_or(0)
+    }
+}
+
+impl Habit for Cycle {
+    type HabitType = CycleState;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
+    fn kind(&self) -> GoalKind {
+        GoalKind::Cycle(
+            self.states
+                .iter()
+                .map(|s| (s.clone(), self.successes.contains(s)))
+                .collect(),
+        )
+    }
+    fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
+        self.stats.get(&date)
+    }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(|s| s.0.clone())
+    }
+    fn get_dates(&self) -> Vec<NaiveDate> {
+        self.stats.keys().copied().collect()
+    }
+    fn insert_entry(&mut self, date: NaiveDate, val: Self::HabitType) {
+        self.stats.insert(date, val);
+    }
+    fn reached_goal(&self, date: NaiveDate) -> bool {
+        self.stats
+            .get(&date)
+            .is_some_and(|state| self.successes.contains(&state.0))
+    }
+    fn remaining(&self, date: NaiveDate) -> u32 {
+        if self.reached_goal(date) { 0 } else { 1 }
+    }
+    fn goal(&self) -> u32 {
+        1
+    }
+    fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
+        if self.states.is_empty() {
+            return;
+        }
+        let current = self.stats.get(&date).map(|s| self.position(s));
+        match event {
+            TrackEvent::Increment => {
+                let next = match current {
+                    Some(i) => (i + 1) % self.states.len(),
+                    None => 0,
+                };
+                self.insert_entry(date, CycleState(self.states[next].clone()));
+            }
+            TrackEvent::Decrement => match current {
+                Some(0) => {
+                    self.stats.remove(&date);
+                }
+                Some(i) => self.insert_entry(date, CycleState(self.states[i - 1].clone())),
+                None => {}
+            },
+            TrackEvent::Set(0) => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(n) => {
+                if let Some(state) = self.states.get(n as usize - 1) {
+                    self.insert_entry(date, CycleState(state.clone()));
+                }
+            }
+        }
+    }
+    /// A state's label, or its position counting from 1; `0` clears the day.
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        match self.states.iter().position(|s| s == input) {
+            Some(i) => Some(i as u32 + 1),
+            None => input
+                .parse::<u32>()
+                .ok()
+                .filter(|&n| n as usize <= self.states.len()),
+        }
+    }
+    fn inner_data_ref(&self) -> &InnerData {
+        &self.inner_data
+    }
+    fn inner_data_mut_ref(&mut self) -> &mut InnerData {
+        &mut self.inner_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn sleep() -> Cycle {
+        Cycle::new(
+            "sleep",
+            vec![
+                ("bad".into(), false),
+                ("ok".into(), true),
+                ("good".into(), true),
+            ],
+        )
+    }
+
+    #[test]
+    fn increment_steps_through_the_states_and_wraps() {
+        let mut h = sleep();
+        let mut seen = vec![];
+        for _ in 0..4 {
+            h.modify(d(1), TrackEvent::Increment);
+            seen.push(h.get_by_date(d(1)).unwrap().0.clone());
+        }
+        assert_eq!(seen, ["bad", "ok", "good", "bad"]);
+    }
+
+    #[test]
+    fn decrement_steps_back_and_then_clears_the_day() {
+        let mut h = sleep();
+        h.insert_entry(d(1), CycleState("ok".into()));
+        h.modify(d(1), TrackEvent::Decrement);
+        assert_eq!(h.get_by_date(d(1)), Some(&CycleState("bad".into())));
+        h.modify(d(1), TrackEvent::Decrement);
+        assert_eq!(h.get_by_date(d(1)), None);
+    }
+
+    #[test]
+    fn only_success_states_reach_the_goal() {
+        let mut h = sleep();
+        h.insert_entry(d(1), CycleState("bad".into()));
+        h.insert_entry(d(2), CycleState("ok".into()));
+        assert!(!h.reached_goal(d(1)));
+        assert!(h.reached_goal(d(2)));
+        assert!(!h.reached_goal(d(3)), "an untracked day is not a success");
+    }
+
+    #[test]
+    fn long_labels_are_drawn_as_three_characters() {
+        assert_eq!(format!("{:^3}", CycleState("good".into())), "goo");
+        assert_eq!(format!("{:^3}", CycleState("✓".into())), " ✓ ");
+    }
+
+    #[test]
+    fn states_survive_a_serde_round_trip() {
+        let mut h = sleep();
+        h.insert_entry(d(1), CycleState("good".into()));
+        let json = serde_json::to_string(&h).unwrap();
+        assert!(json.contains("\"2024-01-01\":\"good\""), "json was {json}");
+        let back: Cycle = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.kind(), h.kind());
+        assert!(back.reached_goal(d(1)));
+    }
+
+    #[test]
+    fn set_takes_a_state_by_label_or_position() {
+        let mut h = sleep();
+        let ok = h.parse_value("ok").unwrap();
+        h.modify(d(1), TrackEvent::Set(ok));
+        assert!(h.reached_goal(d(1)));
+        assert_eq!(h.parse_value("1"), Some(1));
+        assert_eq!(h.parse_value("9"), None);
+        assert_eq!(h.parse_value("great"), None);
+        h.modify(d(1), TrackEvent::Set(0));
+        assert!(h.get_by_date(d(1)).is_none());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::command::{GoalKind, parse_minutes};
+use crate::habit::traits::Habit;
+use crate::habit::{
+    ActiveRange, Freezes, GoalHistory, GoalPeriod, InnerData, Pause, Schedule, Step, TrackEvent,
+};
+use crate::utils::period_total;
+
+/// A span of time in whole minutes, shown as `45m` or `1h05`.
+#[derive(Copy, Clone, Debug, Ord, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Minutes(pub u32);
+
+impl fmt::Display for Minutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (hours, minutes) = (self.0 / 60, self.0 % 60);
+        let s = if hours == 0 {
+            format!("{minutes}m")
+        } else if minutes == 0 {
+            format!("{hours}h")
+        } else {
+            format!("{hours}h{minutes:02}")
+        };
+        f.pad(&s)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Duration {
+    name: String,
+    #[serde(default)]
+    description: String,
+    stats: HashMap<NaiveDate, Minutes>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    goal_history: GoalHistory,
+    #[serde(default)]
+    step: Step,
+    goal: Minutes,
+    #[serde(default)]
+    period: GoalPeriod,
+
+    #[serde(skip)]
+    inner_data: InnerData,
+}
+
+impl Duration {
+    pub fn new(name: impl AsRef<str>, goal: u32) -> Self {
+        Duration {
+            name: name.as_ref().to_owned(),
+            description: String::new(),
+            stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            goal_history: GoalHistory::default(),
+            step: Step::default(),
+            goal: Minutes(goal),
+            period: GoalPeriod::Daily,
+            inner_data: Default::default(),
+        }
+    }
+
+    pub fn with_period(mut self, period: GoalPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// The goal in minutes and period in force on `date`.
+    fn goal_on(&self, date: NaiveDate) -> (u32, GoalPeriod) {
+        self.goal_history.at(date, (self.goal.0, self.period))
+    }
+
+    /// Minutes logged in the week or month containing `date`.
+    fn period_total(&self, date: NaiveDate, period: GoalPeriod) -> u32 {
+        period_total(date, period, |day| {
+            self.stats.get(&day).map(|m| m.0).unwrap_or(0)
+        })
+    }
+}
+
+impl Habit for Duration {
+    type HabitType = Minutes;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
+    fn kind(&self) -> GoalKind {
+        GoalKind::Duration(self.goal.0)
+    }
+    fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
+        self.stats.get(&date)
+    }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(|m| self.format_value(m.0))
+    }
+    fn get_dates(&self) -> Vec<NaiveDate> {
+        self.stats.keys().copied().collect()
+    }
+    fn insert_entry(&mut self, date: NaiveDate, val: Self::HabitType) {
+        *self.stats.entry(date).or_insert(val) = val;
+    }
+    fn reached_goal(&self, date: NaiveDate) -> bool {
+        let (goal, period) = self.goal_on(date);
+        match period {
+            GoalPeriod::Daily => self.stats.get(&date).is_some_and(|val| val.0 >= goal),
+            GoalPeriod::Weekly | GoalPeriod::Monthly => self.period_total(date, period) >= goal,
+        }
+    }
+    fn remaining(&self, date: NaiveDate) -> u32 {
+        let (goal, period) = self.goal_on(date);
+        match period {
+            GoalPeriod::Daily => {
+                let done = self.stats.get(&date).map(|m| m.0).unwrap_or(0);
+                goal.saturating_sub(done)
+            }
+            GoalPeriod::Weekly | GoalPeriod::Monthly => {
+                goal.saturating_sub(self.period_total(date, period))
+            }
+        }
+    }
+    fn goal(&self) -> u32 {
+        self.goal.0
+    }
+    fn goal_at(&self, date: NaiveDate) -> u32 {
+        self.goal_on(date).0
+    }
+    fn period_at(&self, date: NaiveDate) -> GoalPeriod {
+        self.goal_on(date).1
+    }
+    fn set_goal(
+        &mut self,
+        kind: GoalKind,
+        period: GoalPeriod,
+        since: NaiveDate,
+    ) -> Result<(), String> {
+        let GoalKind::Duration(minutes) = kind else {
+            return Err(format!(
+                "`{}` tracks time, give the goal as e.g. 30m",
+                self.name
+            ));
+        };
+        self.goal_history.retire((self.goal.0, self.period), since);
+        self.goal = Minutes(minutes);
+        self.period = period;
+        Ok(())
+    }
+    fn period(&self) -> GoalPeriod {
+        self.period
+    }
+    fn step(&self) -> u32 {
+        self.step.0
+    }
+    fn set_step(&mut self, step: u32) -> Result<(), String> {
+        if step == 0 {
+            return Err(format!("`{}` needs a step above zero", self.name));
+        }
+        self.step = Step(step);
+        Ok(())
+    }
+    fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
+        match event {
+            TrackEvent::Increment => {
+                if let Some(val) = self.stats.get_mut(&date) {
+                    val.0 = val.0.saturating_add(self.step.0)
+                } else {
+                    self.insert_entry(date, Minutes(self.step.0));
+                }
+            }
+            TrackEvent::Decrement => {
+                if let Some(val) = self.stats.get_mut(&date) {
+                    if val.0 > 0 {
+                        val.0 = val.0.saturating_sub(self.step.0)
+                    } else {
+                        self.stats.remove(&date);
+                    }
+                }
+            }
+            TrackEvent::Set(0) => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(minutes) => self.insert_entry(date, Minutes(minutes)),
+        }
+    }
+    /// `45m`, `1h30` or `1:30`, or a bare number of minutes.
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        parse_minutes(input).or_else(|| input.parse().ok())
+    }
+    fn format_value(&self, minutes: u32) -> String {
+        Minutes(minutes).to_string()
+    }
+    fn inner_data_ref(&self) -> &InnerData {
+        &self.inner_data
+    }
+    fn inner_data_mut_ref(&mut self) -> &mut InnerData {
+        &mut self.inner_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 is a Monday, so 01..=07 Jan is one Mon–Sun week.
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn minutes_render_as_hours_and_minutes() {
+        assert_eq!(Minutes(45).to_string(), "45m");
+        assert_eq!(Minutes(65).to_string(), "1h05");
+        assert_eq!(Minutes(120).to_string(), "2h");
+        assert_eq!(format!("{:^5}", Minutes(5)), " 5m  ");
+    }
+
+    #[test]
+    fn daily_goal_is_measured_in_minutes() {
+        let mut h = Duration::new("read", 30);
+        h.insert_entry(d(1), Minutes(20));
+        assert!(!h.reached_goal(d(1)));
+        assert_eq!(h.remaining(d(1)), 10);
+        h.insert_entry(d(1), Minutes(45));
+        assert!(h.reached_goal(d(1)));
+        assert_eq!(h.remaining(d(1)), 0);
+    }
+
+    #[test]
+    fn weekly_goal_aggregates_the_whole_week() {
+        let mut h = Duration::new("read", 90).with_period(GoalPeriod::Weekly);
+        h.insert_entry(d(1), Minutes(45));
+        h.insert_entry(d(4), Minutes(30));
+        assert_eq!(h.remaining(d(7)), 15);
+        h.insert_entry(d(6), Minutes(15));
+        assert!(h.reached_goal(d(2)));
+        assert!(!h.reached_goal(d(8)), "the next week starts from scratch");
+    }
+
+    #[test]
+    fn minutes_serialize_as_plain_numbers() {
+        let mut h = Duration::new("read", 30);
+        h.insert_entry(d(1), Minutes(45));
+        let json = serde_json::to_string(&h).unwrap();
+        assert!(json.contains("\"2024-01-01\":45"), "json was {json}");
+        let back: Duration = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.get_by_date(d(1)), Some(&Minutes(45)));
+    }
+}
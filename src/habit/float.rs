@@ -2,13 +2,15 @@ use std::collections::HashMap;
 use std::fmt;
 use std::ops::{Add, Sub};
 
-use chrono::{Days, NaiveDate};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
 use crate::command::GoalKind;
 use crate::habit::traits::Habit;
-use crate::habit::{GoalPeriod, InnerData, TrackEvent};
-use crate::utils::week_bounds;
+use crate::habit::{
+    ActiveRange, Freezes, GoalHistory, GoalPeriod, InnerData, Pause, Schedule, Step, TrackEvent,
+};
+use crate::utils::period_total;
 
 #[derive(Copy, Clone, Debug, Ord, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct FloatData {
@@ -62,7 +64,7 @@ impl Add for FloatData {
     type Output = Self;
     fn add(self, other: Self) -> Self {
         Self {
-            value: self.value + other.value,
+            value: self.value.saturating_add(other.value),
             precision: self.precision,
         }
     }
@@ -79,12 +81,53 @@ impl Sub for FloatData {
     }
 }
 
+/// Read a decimal like `8.5` or `.25` as an integer in `precision` decimal
+/// places, so `8.5` with a precision of 2 is `850`. `None` when it has more
+/// decimal places than that, or is not a plain decimal.
+pub fn parse_decimal(input: &str, precision: u8) -> Option<u32> {
+    let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || !digits(whole)
+        || !digits(fraction)
+        || fraction.len() > precision as usize
+    {
+        return None;
+    }
+    let scale = 10u32.checked_pow(precision as u32)?;
+    let whole = match whole {
+        "" => 0,
+        w => w.parse::<u32>().ok()?,
+    };
+    let fraction = match fraction {
+        "" => 0,
+        f => f.parse::<u32>().ok()? * 10u32.pow((precision as usize - f.len()) as u32),
+    };
+    whole.checked_mul(scale)?.checked_add(fraction)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Float {
     name: String,
     #[serde(default)]
     description: String,
     stats: HashMap<NaiveDate, FloatData>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    goal_history: GoalHistory,
+    #[serde(default)]
+    step: Step,
     goal: FloatData,
     precision: u8,
     #[serde(default)]
@@ -100,6 +143,14 @@ impl Float {
             name: name.as_ref().to_owned(),
             description: String::new(),
             stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            goal_history: GoalHistory::default(),
+            step: Step::default(),
             goal: FloatData {
                 value: goal,
                 precision,
@@ -115,19 +166,16 @@ impl Float {
         self
     }
 
-    /// Sum of every entry's value in the Mon–Sun week containing `date`.
-    fn week_total(&self, date: NaiveDate) -> u32 {
-        let (monday, sunday) = week_bounds(date);
-        let mut day = monday;
-        let mut total = 0;
-        while day <= sunday {
-            total += self.stats.get(&day).map(|v| v.value).unwrap_or(0);
-            day = match day.checked_add_days(Days::new(1)) {
-                Some(d) => d,
-                None => break,
-            };
-        }
-        total
+    /// The goal value, in the habit's precision, and period in force on `date`.
+    fn goal_on(&self, date: NaiveDate) -> (u32, GoalPeriod) {
+        self.goal_history.at(date, (self.goal.value, self.period))
+    }
+
+    /// Sum of every entry's value in the week or month containing `date`.
+    fn period_total(&self, date: NaiveDate, period: GoalPeriod) -> u32 {
+        period_total(date, period, |day| {
+            self.stats.get(&day).map(|v| v.value).unwrap_or(0)
+        })
     }
 }
 
@@ -140,15 +188,57 @@ impl Habit for Float {
     fn description(&self) -> &str {
         &self.description
     }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
     fn set_description(&mut self, description: String) {
         self.description = description;
     }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
     fn kind(&self) -> GoalKind {
         GoalKind::Float(self.goal.value, self.goal.precision)
     }
     fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
         self.stats.get(&date)
     }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(|v| self.format_value(v.value))
+    }
     fn get_dates(&self) -> Vec<NaiveDate> {
         self.stats.keys().copied().collect()
     }
@@ -156,41 +246,91 @@ impl Habit for Float {
         *self.stats.entry(date).or_insert(val) = val;
     }
     fn reached_goal(&self, date: NaiveDate) -> bool {
-        match self.period {
-            GoalPeriod::Daily => self.stats.get(&date).is_some_and(|val| val >= &self.goal),
-            GoalPeriod::Weekly => self.week_total(date) >= self.goal.value,
+        let (goal, period) = self.goal_on(date);
+        match period {
+            GoalPeriod::Daily => self.stats.get(&date).is_some_and(|val| val.value >= goal),
+            GoalPeriod::Weekly | GoalPeriod::Monthly => self.period_total(date, period) >= goal,
         }
     }
     fn remaining(&self, date: NaiveDate) -> u32 {
-        match self.period {
+        let (goal, period) = self.goal_on(date);
+        match period {
             GoalPeriod::Daily => {
-                if self.reached_goal(date) {
-                    0
-                } else if let Some(&val) = self.stats.get(&date) {
-                    (self.goal - val).value
-                } else {
-                    self.goal.value
-                }
+                goal.saturating_sub(self.stats.get(&date).map(|v| v.value).unwrap_or(0))
+            }
+            GoalPeriod::Weekly | GoalPeriod::Monthly => {
+                goal.saturating_sub(self.period_total(date, period))
             }
-            GoalPeriod::Weekly => self.goal.value.saturating_sub(self.week_total(date)),
         }
     }
     fn goal(&self) -> u32 {
         self.goal.value
     }
+    fn goal_at(&self, date: NaiveDate) -> u32 {
+        self.goal_on(date).0
+    }
+    fn period_at(&self, date: NaiveDate) -> GoalPeriod {
+        self.goal_on(date).1
+    }
+    fn set_goal(
+        &mut self,
+        kind: GoalKind,
+        period: GoalPeriod,
+        since: NaiveDate,
+    ) -> Result<(), String> {
+        let scale = |places: u8| 10u32.checked_pow(places as u32);
+        let value = match kind {
+            GoalKind::Float(value, places) if places <= self.precision => {
+                scale(self.precision - places).and_then(|s| value.checked_mul(s))
+            }
+            GoalKind::Float(..) => {
+                return Err(format!(
+                    "`{}` keeps {} decimal place(s), the goal can't have more",
+                    self.name, self.precision
+                ));
+            }
+            GoalKind::Count(n) => scale(self.precision).and_then(|s| n.checked_mul(s)),
+            GoalKind::Bit => scale(self.precision),
+            _ => {
+                return Err(format!(
+                    "`{}` tracks a number, so its goal is one",
+                    self.name
+                ));
+            }
+        }
+        .ok_or_else(|| format!("That goal is too large for `{}`", self.name))?;
+        self.goal_history
+            .retire((self.goal.value, self.period), since);
+        self.goal = FloatData {
+            value,
+            precision: self.precision,
+        };
+        self.period = period;
+        Ok(())
+    }
     fn period(&self) -> GoalPeriod {
         self.period
     }
+    fn step(&self) -> u32 {
+        self.step.0
+    }
+    fn set_step(&mut self, step: u32) -> Result<(), String> {
+        if step == 0 {
+            return Err(format!("`{}` needs a step above zero", self.name));
+        }
+        self.step = Step(step);
+        Ok(())
+    }
     fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
         match event {
             TrackEvent::Increment => {
                 if let Some(val) = self.stats.get_mut(&date) {
-                    *val = val.add(1);
+                    *val = val.add(self.step.0);
                 } else {
                     self.insert_entry(
                         date,
                         FloatData {
-                            value: 1,
+                            value: self.step.0,
                             precision: self.precision,
                         },
                     )
@@ -199,12 +339,36 @@ impl Habit for Float {
             TrackEvent::Decrement => {
                 if let Some(val) = self.stats.get_mut(&date) {
                     if *val > FloatData::zero() {
-                        *val = val.sub(1);
+                        *val = val.sub(self.step.0);
                     } else {
                         self.stats.remove(&date);
                     };
                 }
             }
+            TrackEvent::Set(0) => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(value) => self.insert_entry(
+                date,
+                FloatData {
+                    value,
+                    precision: self.precision,
+                },
+            ),
+        }
+    }
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        parse_decimal(input, self.precision)
+    }
+    fn format_value(&self, value: u32) -> String {
+        let width = self.precision as usize;
+        let scale = 10u32.pow(self.precision as u32);
+        match (value / scale, value % scale) {
+            (whole, 0) => whole.to_string(),
+            (whole, fraction) => {
+                let fraction = format!("{fraction:0width$}");
+                format!("{whole}.{}", fraction.trim_end_matches('0'))
+            }
         }
     }
     fn inner_data_ref(&self) -> &InnerData {
@@ -214,3 +378,93 @@ impl Habit for Float {
         &mut self.inner_data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn goal_change_is_scaled_to_the_habits_precision() {
+        let mut h = Float::new("run", 25, 1); // 2.5
+        h.set_goal(GoalKind::Float(3, 0), GoalPeriod::Daily, d(10))
+            .unwrap();
+        assert_eq!(h.goal(), 30);
+        assert_eq!(h.goal_at(d(9)), 25);
+
+        h.set_goal(GoalKind::Count(4), GoalPeriod::Daily, d(11))
+            .unwrap();
+        assert_eq!(h.goal(), 40);
+        assert!(
+            h.set_goal(GoalKind::Float(425, 2), GoalPeriod::Daily, d(12))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn goals_too_large_to_scale_are_refused() {
+        let mut h = Float::new("run", 25, 1);
+        assert!(
+            h.set_goal(GoalKind::Count(u32::MAX), GoalPeriod::Daily, d(10))
+                .is_err()
+        );
+        assert!(
+            h.set_goal(GoalKind::Float(u32::MAX / 5, 0), GoalPeriod::Daily, d(10))
+                .is_err()
+        );
+        assert_eq!(h.goal(), 25, "the goal is left as it was");
+    }
+
+    #[test]
+    fn earlier_days_keep_the_old_goal() {
+        let mut h = Float::new("run", 25, 1);
+        let km = |value| FloatData {
+            value,
+            precision: 1,
+        };
+        h.insert_entry(d(5), km(26));
+        h.insert_entry(d(12), km(26));
+        h.set_goal(GoalKind::Float(3, 0), GoalPeriod::Daily, d(10))
+            .unwrap();
+
+        assert!(h.reached_goal(d(5)));
+        assert!(!h.reached_goal(d(12)));
+        assert_eq!(h.remaining(d(12)), 4);
+    }
+
+    #[test]
+    fn decimals_are_read_in_the_habits_precision() {
+        assert_eq!(parse_decimal("8.5", 1), Some(85));
+        assert_eq!(parse_decimal("8.5", 2), Some(850));
+        assert_eq!(parse_decimal(".25", 2), Some(25));
+        assert_eq!(parse_decimal("8", 1), Some(80));
+        assert_eq!(parse_decimal("8.", 1), Some(80));
+        assert_eq!(parse_decimal("8.55", 1), None);
+        assert_eq!(parse_decimal("8,5", 1), None);
+        assert_eq!(parse_decimal(".", 1), None);
+        assert_eq!(parse_decimal("-1", 1), None);
+    }
+
+    #[test]
+    fn set_replaces_the_days_value() {
+        let mut h = Float::new("run", 25, 1);
+        h.modify(d(1), TrackEvent::Increment);
+        let value = h.parse_value("8.5").unwrap();
+        h.modify(d(1), TrackEvent::Set(value));
+        assert_eq!(h.get_by_date(d(1)).unwrap().value, 85);
+        h.modify(d(1), TrackEvent::Set(0));
+        assert!(h.get_by_date(d(1)).is_none());
+    }
+
+    #[test]
+    fn values_are_written_the_way_they_are_read() {
+        let h = Float::new("run", 25, 2);
+        for input in ["8.5", "0.25", "8"] {
+            let value = h.parse_value(input).unwrap();
+            assert_eq!(h.format_value(value), input);
+        }
+    }
+}
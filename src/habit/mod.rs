@@ -10,8 +10,25 @@ pub use bit::Bit;
 mod float;
 pub use float::Float;
 
+mod addiction;
+pub use addiction::Addiction;
+
+mod duration;
+pub use duration::{Duration, Minutes};
+
+mod cycle;
+pub use cycle::Cycle;
+
+mod rating;
+pub use rating::Rating;
+
+mod convert;
+pub use convert::convert;
+
 mod prelude;
-pub use prelude::{GoalPeriod, TrackEvent, ViewMode};
+pub use prelude::{
+    ActiveRange, Freezes, GoalHistory, GoalPeriod, Pause, Schedule, Step, TrackEvent, ViewMode,
+};
 
 use crate::app::Cursor;
 
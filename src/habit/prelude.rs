@@ -1,10 +1,14 @@
+use chrono::{Datelike, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TrackEvent {
     Increment,
     Decrement,
+    /// Replace the day's entry with a value in the habit's own unit, as
+    /// returned by `Habit::parse_value`.
+    Set(u32),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
@@ -33,14 +37,18 @@ impl fmt::Display for ViewMode {
     }
 }
 
-/// Whether a habit's `goal` is a per-day target or a per-week (Mon–Sun) one.
+/// Whether a habit's `goal` is a per-day target, a per-week (Mon–Sun) one or a
+/// per-calendar-month one.
 /// Persisted on each habit; `#[serde(default)]` means existing records load as
 /// `Daily`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Default,
+)]
 pub enum GoalPeriod {
     #[default]
     Daily,
     Weekly,
+    Monthly,
 }
 
 impl fmt::Display for GoalPeriod {
@@ -48,6 +56,221 @@ impl fmt::Display for GoalPeriod {
         match self {
             GoalPeriod::Daily => write!(f, "day"),
             GoalPeriod::Weekly => write!(f, "week"),
+            GoalPeriod::Monthly => write!(f, "month"),
+        }
+    }
+}
+
+/// A goal a habit had before it was changed: `goal` per `period` applied to
+/// every date up to and including `until`. Goals are stored raw, as a count,
+/// minutes, a limit, or a `Float` value in the habit's precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoalRevision {
+    pub until: NaiveDate,
+    pub goal: u32,
+    #[serde(default)]
+    pub period: GoalPeriod,
+}
+
+/// The goals a habit had before its current one, oldest first, so that each
+/// date is judged against the goal that applied then. Empty for habits whose
+/// goal was never changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GoalHistory(Vec<GoalRevision>);
+
+impl GoalHistory {
+    /// The goal and period in force on `date`, given the `current` ones.
+    pub fn at(&self, date: NaiveDate, current: (u32, GoalPeriod)) -> (u32, GoalPeriod) {
+        self.0
+            .iter()
+            .find(|r| date <= r.until)
+            .map_or(current, |r| (r.goal, r.period))
+    }
+
+    /// Keep the `old` goal for every date before `since`. A goal replaced
+    /// again on the same day never applied to a whole day, so it is dropped.
+    pub fn retire(&mut self, (goal, period): (u32, GoalPeriod), since: NaiveDate) {
+        let Some(until) = since.pred_opt() else {
+            return;
+        };
+        if self.0.last().is_some_and(|last| last.until >= until) {
+            return;
+        }
+        self.0.push(GoalRevision {
+            until,
+            goal,
+            period,
+        });
+    }
+}
+
+/// How much one `Increment` or `Decrement` changes a day's value by, in the
+/// habit's own unit (hundredths for a `Float` with 2 decimal places). Records
+/// from before steps existed step by 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Step(pub u32);
+
+impl Default for Step {
+    fn default() -> Self {
+        Step(1)
+    }
+}
+
+/// A budget of missed periods, `count` per `per` (e.g. 2 rest days a week),
+/// that stats bridge instead of breaking the streak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Freezes {
+    pub count: u32,
+    pub per: GoalPeriod,
+}
+
+impl fmt::Display for Freezes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} per {}", self.count, self.per)
+    }
+}
+
+/// The span of days a habit is tracked over: from `start`, the day it was
+/// added, up to an optional `end`. Days outside it are neither due nor missed.
+/// Records written before habits had a start leave it empty, and their first
+/// entry stands in for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct ActiveRange {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<NaiveDate>,
+}
+
+impl ActiveRange {
+    pub fn starting(start: NaiveDate) -> Self {
+        ActiveRange {
+            start: Some(start),
+            end: None,
+        }
+    }
+
+    /// Whether `date` lies within the range; an open bound admits every date.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| start <= date) && self.end.is_none_or(|end| date <= end)
+    }
+}
+
+impl fmt::Display for ActiveRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => write!(f, "{start} to {end}"),
+            (Some(start), None) => write!(f, "since {start}"),
+            (None, Some(end)) => write!(f, "until {end}"),
+            (None, None) => write!(f, "always"),
+        }
+    }
+}
+
+/// Days, `from` through `to`, on which a habit is paused for sickness or
+/// travel. Paused days are neither due nor missed, so streaks run across them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pause {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl Pause {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        Pause {
+            from: from.min(to),
+            to: to.max(from),
+        }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+}
+
+impl fmt::Display for Pause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)
+        } else {
+            write!(f, "{} to {}", self.from, self.to)
+        }
+    }
+}
+
+/// When a habit is due: on certain weekdays (days outside the schedule are
+/// neither missed nor counted; empty, the `#[serde(default)]`, means every
+/// day), or `every` N days after its last completion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Schedule {
+    Weekdays(Vec<Weekday>),
+    Interval { every: u32 },
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule::every_day()
+    }
+}
+
+impl Schedule {
+    pub fn new(mut days: Vec<Weekday>) -> Self {
+        days.sort_by_key(|d| d.num_days_from_monday());
+        days.dedup();
+        // all seven days is the same as no schedule at all
+        if days.len() == 7 {
+            days.clear();
+        }
+        Schedule::Weekdays(days)
+    }
+
+    pub fn every_day() -> Self {
+        Schedule::Weekdays(Vec::new())
+    }
+
+    /// Due `n` days after the last completion. Every 1 day is plain daily.
+    pub fn every(n: u32) -> Self {
+        if n <= 1 {
+            Schedule::every_day()
+        } else {
+            Schedule::Interval { every: n }
+        }
+    }
+
+    pub fn is_every_day(&self) -> bool {
+        matches!(self, Schedule::Weekdays(days) if days.is_empty())
+    }
+
+    /// The interval in days for an every-N-days habit.
+    pub fn interval(&self) -> Option<u32> {
+        match self {
+            Schedule::Interval { every } => Some(*every),
+            Schedule::Weekdays(_) => None,
+        }
+    }
+
+    /// Whether `date` falls on a scheduled weekday. Interval schedules depend
+    /// on the last completion, so they include every date here.
+    pub fn includes(&self, date: NaiveDate) -> bool {
+        match self {
+            Schedule::Weekdays(days) => days.is_empty() || days.contains(&date.weekday()),
+            Schedule::Interval { .. } => true,
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Weekdays(days) if days.is_empty() => write!(f, "every day"),
+            Schedule::Weekdays(days) => {
+                let days: Vec<String> = days.iter().map(|d| d.to_string()).collect();
+                write!(f, "{}", days.join(", "))
+            }
+            Schedule::Interval { every } => write!(f, "every {every} days"),
         }
     }
 }
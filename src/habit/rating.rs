@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use cursive::theme::{BaseColor, Color};
+use serde::{Deserialize, Serialize};
+
+use crate::command::GoalKind;
+use crate::habit::traits::Habit;
+use crate::habit::{ActiveRange, Freezes, InnerData, Pause, Schedule, TrackEvent};
+use crate::stats::{RatingStats, rating_stats};
+
+/// A bounded score given once a day, e.g. mood on a 1–5 scale. Values are
+/// clamped to `min..=max`, and any rated day counts as reached.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Rating {
+    name: String,
+    #[serde(default)]
+    description: String,
+    stats: HashMap<NaiveDate, u32>,
+    #[serde(default)]
+    notes: HashMap<NaiveDate, String>,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(flatten)]
+    range: ActiveRange,
+    #[serde(default)]
+    pauses: Vec<Pause>,
+    #[serde(default)]
+    freezes: Option<Freezes>,
+    #[serde(default)]
+    group: Option<String>,
+    min: u32,
+    max: u32,
+
+    #[serde(skip)]
+    inner_data: InnerData,
+}
+
+impl Rating {
+    pub fn new(name: impl AsRef<str>, min: u32, max: u32) -> Self {
+        Rating {
+            name: name.as_ref().to_owned(),
+            description: String::new(),
+            stats: HashMap::new(),
+            notes: HashMap::new(),
+            schedule: Schedule::every_day(),
+            range: ActiveRange::default(),
+            pauses: Vec::new(),
+            freezes: None,
+            group: None,
+            min: min.min(max),
+            max: max.max(min),
+            inner_data: Default::default(),
+        }
+    }
+}
+
+impl Habit for Rating {
+    type HabitType = u32;
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    fn set_description(&mut self, description: String) {
+        self.description = description;
+    }
+    fn notes(&self) -> &HashMap<NaiveDate, String> {
+        &self.notes
+    }
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String> {
+        &mut self.notes
+    }
+    fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+    fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+    fn range(&self) -> ActiveRange {
+        self.range
+    }
+    fn set_range(&mut self, range: ActiveRange) {
+        self.range = range;
+    }
+    fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+    fn pauses_mut(&mut self) -> &mut Vec<Pause> {
+        &mut self.pauses
+    }
+    fn freezes(&self) -> Option<Freezes> {
+        self.freezes
+    }
+    fn set_freezes(&mut self, freezes: Option<Freezes>) {
+        self.freezes = freezes;
+    }
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    fn set_group(&mut self, group: Option<String>) {
+        self.group = group;
+    }
+    fn kind(&self) -> GoalKind {
+        GoalKind::Rating(self.min, self.max)
+    }
+    fn get_by_date(&self, date: NaiveDate) -> Option<&Self::HabitType> {
+        self.stats.get(&date)
+    }
+    fn entry_value(&self, date: NaiveDate) -> Option<String> {
+        self.stats.get(&date).map(u32::to_string)
+    }
+    fn get_dates(&self) -> Vec<NaiveDate> {
+        self.stats.keys().copied().collect()
+    }
+    fn insert_entry(&mut self, date: NaiveDate, val: Self::HabitType) {
+        self.stats.insert(date, val.clamp(self.min, self.max));
+    }
+    fn reached_goal(&self, date: NaiveDate) -> bool {
+        self.stats.contains_key(&date)
+    }
+    fn remaining(&self, date: NaiveDate) -> u32 {
+        if self.reached_goal(date) { 0 } else { 1 }
+    }
+    fn goal(&self) -> u32 {
+        1
+    }
+    /// Low ratings are drawn red, middling ones yellow and high ones green.
+    fn entry_color(&self, date: NaiveDate) -> Option<Color> {
+        const SCALE: [Color; 5] = [
+            Color::Dark(BaseColor::Red),
+            Color::Light(BaseColor::Red),
+            Color::Dark(BaseColor::Yellow),
+            Color::Light(BaseColor::Green),
+            Color::Dark(BaseColor::Green),
+        ];
+        let value = *self.stats.get(&date)?;
+        let span = (self.max - self.min).max(1) as f64;
+        let ratio = (value.saturating_sub(self.min)) as f64 / span;
+        let idx = (ratio * (SCALE.len() - 1) as f64).round() as usize;
+        Some(SCALE[idx.min(SCALE.len() - 1)])
+    }
+    fn rating_summary(&self) -> Option<RatingStats> {
+        let values: Vec<(NaiveDate, u32)> = self.stats.iter().map(|(&d, &v)| (d, v)).collect();
+        Some(rating_stats(&values, self.min, self.max))
+    }
+    fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
+        match event {
+            TrackEvent::Increment => {
+                let next = match self.stats.get(&date) {
+                    Some(val) => val + 1,
+                    None => self.min,
+                };
+                self.insert_entry(date, next);
+            }
+            TrackEvent::Decrement => {
+                if let Some(val) = self.stats.get_mut(&date) {
+                    if *val > self.min {
+                        *val -= 1
+                    } else {
+                        self.stats.remove(&date);
+                    }
+                }
+            }
+            // below the scale, as `0` is for a scale from 1, clears the day
+            TrackEvent::Set(val) if val < self.min => {
+                self.stats.remove(&date);
+            }
+            TrackEvent::Set(val) => self.insert_entry(date, val),
+        }
+    }
+    /// Only values on the scale are accepted, rather than clamped onto it,
+    /// and `0`, which clears the day unless the scale starts at 0.
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        input
+            .parse()
+            .ok()
+            .filter(|&v| v == 0 || (self.min..=self.max).contains(&v))
+    }
+    fn inner_data_ref(&self) -> &InnerData {
+        &self.inner_data
+    }
+    fn inner_data_mut_ref(&mut self) -> &mut InnerData {
+        &mut self.inner_data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn values_are_clamped_to_the_scale() {
+        let mut h = Rating::new("mood", 1, 5);
+        h.insert_entry(d(1), 9);
+        h.insert_entry(d(2), 0);
+        assert_eq!(h.get_by_date(d(1)), Some(&5));
+        assert_eq!(h.get_by_date(d(2)), Some(&1));
+    }
+
+    #[test]
+    fn increment_starts_at_the_bottom_and_stops_at_the_top() {
+        let mut h = Rating::new("mood", 1, 3);
+        for _ in 0..5 {
+            h.modify(d(1), TrackEvent::Increment);
+        }
+        assert_eq!(h.get_by_date(d(1)), Some(&3));
+        for _ in 0..3 {
+            h.modify(d(1), TrackEvent::Decrement);
+        }
+        assert_eq!(
+            h.get_by_date(d(1)),
+            None,
+            "decrementing the minimum clears it"
+        );
+    }
+
+    #[test]
+    fn setting_zero_clears_the_day_unless_it_is_on_the_scale() {
+        let mut h = Rating::new("mood", 1, 5);
+        h.insert_entry(d(1), 4);
+        assert_eq!(h.parse_value("0"), Some(0));
+        h.modify(d(1), TrackEvent::Set(0));
+        assert_eq!(h.get_by_date(d(1)), None);
+        assert_eq!(h.parse_value("6"), None);
+
+        let mut h = Rating::new("energy", 0, 10);
+        h.modify(d(1), TrackEvent::Set(0));
+        assert_eq!(h.get_by_date(d(1)), Some(&0), "0 is a score here");
+    }
+
+    #[test]
+    fn entry_colour_runs_from_red_to_green() {
+        let mut h = Rating::new("mood", 1, 5);
+        h.insert_entry(d(1), 1);
+        h.insert_entry(d(2), 5);
+        assert_eq!(h.entry_color(d(1)), Some(Color::Dark(BaseColor::Red)));
+        assert_eq!(h.entry_color(d(2)), Some(Color::Dark(BaseColor::Green)));
+        assert_eq!(h.entry_color(d(3)), None);
+    }
+
+    #[test]
+    fn scale_survives_a_serde_round_trip() {
+        let h = Rating::new("energy", 0, 10);
+        let json = serde_json::to_string(&h).unwrap();
+        let back: Rating = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.kind(), GoalKind::Rating(0, 10));
+    }
+}
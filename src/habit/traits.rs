@@ -1,11 +1,18 @@
+use std::collections::{HashMap, HashSet};
+
 use chrono::{Datelike, Days, Local, NaiveDate};
 use cursive::direction::Direction;
 use cursive::event::{Event, EventResult};
+use cursive::theme::Color;
 use cursive::view::CannotFocus;
 use cursive::{Printer, Vec2};
 
 use crate::command::GoalKind;
-use crate::habit::{Bit, Count, Float, GoalPeriod, InnerData, TrackEvent};
+use crate::habit::{
+    ActiveRange, Addiction, Bit, Count, Cycle, Duration, Float, Freezes, GoalPeriod, InnerData,
+    Pause, Rating, Schedule, TrackEvent,
+};
+use crate::stats::{HabitStats, RatingStats, habit_stats, interval_stats};
 use crate::views::ShadowView;
 
 pub trait Habit {
@@ -18,17 +25,287 @@ pub trait Habit {
     fn modify(&mut self, date: NaiveDate, event: TrackEvent);
     fn name(&self) -> &str;
     fn description(&self) -> &str;
+    fn set_name(&mut self, name: String);
     fn set_description(&mut self, description: String);
+    fn notes(&self) -> &HashMap<NaiveDate, String>;
+    fn notes_mut(&mut self) -> &mut HashMap<NaiveDate, String>;
+    fn schedule(&self) -> &Schedule;
+    fn set_schedule(&mut self, schedule: Schedule);
+    fn range(&self) -> ActiveRange;
+    fn set_range(&mut self, range: ActiveRange);
+    fn pauses(&self) -> &[Pause];
+    fn pauses_mut(&mut self) -> &mut Vec<Pause>;
+    fn freezes(&self) -> Option<Freezes>;
+    fn set_freezes(&mut self, freezes: Option<Freezes>);
+    fn group(&self) -> Option<&str>;
+    fn set_group(&mut self, group: Option<String>);
     fn reached_goal(&self, date: NaiveDate) -> bool;
     fn remaining(&self, date: NaiveDate) -> u32;
     fn kind(&self) -> GoalKind;
+    /// The value recorded on `date`, written the way `parse_value` reads it:
+    /// `2.5`, `1h30`, `yes`, or a state's label. `None` without an entry.
+    fn entry_value(&self, date: NaiveDate) -> Option<String>;
+
+    /// Attach `note` to `date`, replacing any earlier one. An empty note
+    /// removes it.
+    fn set_note(&mut self, date: NaiveDate, note: String) {
+        if note.is_empty() {
+            self.notes_mut().remove(&date);
+        } else {
+            self.notes_mut().insert(date, note);
+        }
+    }
+
+    /// Pause the habit over `pause`. A span that is already recorded is not
+    /// added twice.
+    fn add_pause(&mut self, pause: Pause) {
+        if !self.pauses().contains(&pause) {
+            self.pauses_mut().push(pause);
+        }
+    }
+
+    fn is_paused(&self, date: NaiveDate) -> bool {
+        self.pauses().iter().any(|p| p.contains(date))
+    }
+
+    /// Unpause the days of `span`, shortening or splitting the pauses that
+    /// cover them.
+    fn remove_pause(&mut self, span: Pause) {
+        let mut kept = Vec::new();
+        for &p in self.pauses() {
+            if p.to < span.from || span.to < p.from {
+                kept.push(p);
+                continue;
+            }
+            if let Some(before) = span.from.pred_opt().filter(|&d| p.from <= d) {
+                kept.push(Pause::new(p.from, before));
+            }
+            if let Some(after) = span.to.succ_opt().filter(|&d| d <= p.to) {
+                kept.push(Pause::new(after, p.to));
+            }
+        }
+        *self.pauses_mut() = kept;
+    }
+
+    /// Days after `from` up to and including `to` that were not paused.
+    /// Overlapping pauses are merged first, so no day is taken off twice.
+    fn unpaused_days(&self, from: NaiveDate, to: NaiveDate) -> i64 {
+        let Some(start) = from.succ_opt() else {
+            return 0;
+        };
+        let mut spans: Vec<(NaiveDate, NaiveDate)> = self
+            .pauses()
+            .iter()
+            .map(|p| (p.from.max(start), p.to.min(to)))
+            .filter(|(a, b)| a <= b)
+            .collect();
+        spans.sort_unstable();
+        let mut paused = 0;
+        let mut counted_to: Option<NaiveDate> = None;
+        for (a, b) in spans {
+            let a = match counted_to.and_then(|c| c.succ_opt()) {
+                Some(next) => a.max(next),
+                None => a,
+            };
+            if a <= b {
+                paused += (b - a).num_days() + 1;
+            }
+            counted_to = Some(counted_to.map_or(b, |c| c.max(b)));
+        }
+        (to - from).num_days() - paused
+    }
+
+    /// Whether the habit is due on `date`. Days outside its active range, its
+    /// weekday schedule or on a pause are drawn as neutral and neither missed
+    /// nor counted in stats. An every-N-days habit is due once N unpaused days
+    /// have passed since it was last done.
+    fn is_due(&self, date: NaiveDate) -> bool {
+        if !self.is_active(date) || self.is_paused(date) {
+            return false;
+        }
+        match self.schedule().interval() {
+            Some(every) => self
+                .last_reached_before(date)
+                .is_none_or(|last| self.unpaused_days(last, date) >= every as i64),
+            None => self.schedule().includes(date),
+        }
+    }
+
+    /// The days from `from` up to `to` on which the habit is due, as `is_due`
+    /// tells them, found in one pass over its history rather than by looking
+    /// back for the last completion one day at a time.
+    fn due_dates(&self, from: NaiveDate, to: NaiveDate) -> HashSet<NaiveDate> {
+        let days = from.iter_days().take_while(|&d| d <= to);
+        let Some(every) = self.schedule().interval() else {
+            return days.filter(|&d| self.is_due(d)).collect();
+        };
+        let mut reached = self.reached_dates();
+        reached.extend(self.inner_data_ref().archived_reached.iter().copied());
+        reached.sort_unstable();
+        reached.dedup();
+        let mut next = reached.partition_point(|&d| d < from);
+        // unpaused days since the last completion, up to the day before
+        let mut since_last = next.checked_sub(1).map(|i| {
+            let before = from.pred_opt().unwrap_or(from);
+            self.unpaused_days(reached[i], before)
+        });
+        let mut due = HashSet::new();
+        for day in days {
+            let paused = self.is_paused(day);
+            if !paused {
+                since_last = since_last.map(|n| n + 1);
+            }
+            if !paused && self.is_active(day) && since_last.is_none_or(|n| n >= every as i64) {
+                due.insert(day);
+            }
+            if reached.get(next) == Some(&day) {
+                next += 1;
+                since_last = Some(0);
+            }
+        }
+        due
+    }
+
+    /// Whether `date` falls within the habit's active range. Without a recorded
+    /// start, the first entry (archived months included) is taken as one;
+    /// habits read from the habit file have it recorded when loaded.
+    fn is_active(&self, date: NaiveDate) -> bool {
+        let range = self.range();
+        let start = range.start.or_else(|| {
+            self.get_dates()
+                .into_iter()
+                .chain(self.inner_data_ref().archived_reached.iter().copied())
+                .min()
+        });
+        ActiveRange { start, ..range }.contains(date)
+    }
+
+    /// Whether a past day it was due on counts as missed: by default, when it
+    /// has no entry at all.
+    fn missed_on(&self, date: NaiveDate) -> bool {
+        self.get_by_date(date).is_none()
+    }
+
+    /// Days from `today` until an every-N-days habit is next due: `0` when it
+    /// is due today, negative when overdue. `None` for any other schedule.
+    fn days_until_due(&self, today: NaiveDate) -> Option<i64> {
+        let every = self.schedule().interval()?;
+        let last = today
+            .succ_opt()
+            .and_then(|tomorrow| self.last_reached_before(tomorrow));
+        Some(last.map_or(0, |last| every as i64 - self.unpaused_days(last, today)))
+    }
+
+    /// The most recent day before `date` that reached the goal, archived
+    /// months included.
+    fn last_reached_before(&self, date: NaiveDate) -> Option<NaiveDate> {
+        self.reached_dates()
+            .into_iter()
+            .chain(self.inner_data_ref().archived_reached.iter().copied())
+            .filter(|&d| d < date)
+            .max()
+    }
+
+    /// Every date on which the goal was reached. Defaults to the recorded
+    /// dates that meet it; `Addiction` also counts empty past days.
+    fn reached_dates(&self) -> Vec<NaiveDate> {
+        self.get_dates()
+            .into_iter()
+            .filter(|&d| self.reached_goal(d))
+            .collect()
+    }
+
+    /// Stats as of `to` over the days from `from`, or else the start of the
+    /// active range, up to `to`, archived months included. Periods missed
+    /// after that start count against the completion rate even before the
+    /// first reached one; days reached before it are not counted.
+    fn stats_between(&self, from: Option<NaiveDate>, to: NaiveDate) -> HabitStats {
+        let since = from.max(self.range().start);
+        let mut reached = self.reached_dates();
+        reached.extend(self.inner_data_ref().archived_reached.iter().copied());
+        reached.retain(|&d| d <= to && since.is_none_or(|since| d >= since));
+        reached.sort_unstable();
+        reached.dedup();
+        match self.schedule().interval() {
+            Some(every) => interval_stats(&reached, to, every, since, |d| self.is_paused(d)),
+            None => habit_stats(&reached, to, self.period(), self.freezes(), since, |d| {
+                self.is_due(d)
+            }),
+        }
+    }
+
+    /// The goal in force on `date`. Defaults to the current one; habits whose
+    /// goal can be changed remember the earlier ones.
+    fn goal_at(&self, _date: NaiveDate) -> u32 {
+        self.goal()
+    }
+
+    /// The daily limit in force on `date`, for habits that have one rather
+    /// than a goal; see `Addiction`, whose `goal_at` is a yes/no target.
+    fn limit_at(&self, _date: NaiveDate) -> Option<u32> {
+        None
+    }
+
+    /// Change the goal to `kind` per `period` from `since` on. Earlier dates
+    /// are still judged against the goal that applied then. Only habits with a
+    /// numeric goal can change it.
+    fn set_goal(
+        &mut self,
+        _kind: GoalKind,
+        _period: GoalPeriod,
+        _since: NaiveDate,
+    ) -> Result<(), String> {
+        Err(format!("`{}` has no goal to change", self.name()))
+    }
 
     /// Whether `goal` is a per-day or per-week target. Defaults to daily; only
-    /// numeric habits (`Count`, `Float`) override it.
+    /// numeric habits (`Count`, `Float`, `Duration`) override it.
     fn period(&self) -> GoalPeriod {
         GoalPeriod::Daily
     }
 
+    /// The period of the goal in force on `date`, alongside `goal_at`.
+    fn period_at(&self, _date: NaiveDate) -> GoalPeriod {
+        self.period()
+    }
+
+    /// Write a value in the unit `parse_value` reads, e.g. `2.5` or `1h30`.
+    fn format_value(&self, value: u32) -> String {
+        value.to_string()
+    }
+
+    /// How much `Increment` and `Decrement` change a day's value by, in the
+    /// same unit as `parse_value`. Only numeric habits have a step other
+    /// than 1.
+    fn step(&self) -> u32 {
+        1
+    }
+
+    /// Change the step. Only `Count`, `Float`, `Duration` and `Addiction`
+    /// habits have one to change.
+    fn set_step(&mut self, _step: u32) -> Result<(), String> {
+        Err(format!("`{}` has no step to change", self.name()))
+    }
+
+    /// Read a typed-in value into the unit `TrackEvent::Set` takes. Defaults
+    /// to a whole number; kinds with their own unit (decimals, minutes, named
+    /// states) override it. `None` when `input` is not a value of this habit.
+    fn parse_value(&self, input: &str) -> Option<u32> {
+        input.parse().ok()
+    }
+
+    /// Colour to draw the entry on `date` in, overriding the reached/todo
+    /// colours. Only `Rating` colours its entries by value.
+    fn entry_color(&self, _date: NaiveDate) -> Option<Color> {
+        None
+    }
+
+    /// Average and distribution of a `Rating` habit's values; `None` for
+    /// every other kind.
+    fn rating_summary(&self) -> Option<RatingStats> {
+        None
+    }
+
     fn inner_data_ref(&self) -> &InnerData;
     fn inner_data_mut_ref(&mut self) -> &mut InnerData;
 }
@@ -38,12 +315,46 @@ pub trait HabitWrapper: erased_serde::Serialize + Sync + Send {
     fn draw(&self, printer: &Printer);
     fn get_dates(&self) -> Vec<NaiveDate>;
     fn goal(&self) -> u32;
+    fn goal_at(&self, date: NaiveDate) -> u32;
+    fn set_goal(
+        &mut self,
+        kind: GoalKind,
+        period: GoalPeriod,
+        since: NaiveDate,
+    ) -> Result<(), String>;
     fn kind(&self) -> GoalKind;
     fn period(&self) -> GoalPeriod;
+    fn period_at(&self, date: NaiveDate) -> GoalPeriod;
+    fn limit_at(&self, date: NaiveDate) -> Option<u32>;
+    fn entry_value(&self, date: NaiveDate) -> Option<String>;
+    fn reached_dates(&self) -> Vec<NaiveDate>;
+    fn rating_summary(&self) -> Option<RatingStats>;
     fn modify(&mut self, date: NaiveDate, event: TrackEvent);
+    fn parse_value(&self, input: &str) -> Option<u32>;
+    fn format_value(&self, value: u32) -> String;
+    fn step(&self) -> u32;
+    fn set_step(&mut self, step: u32) -> Result<(), String>;
     fn name(&self) -> &str;
     fn description(&self) -> &str;
+    fn set_name(&mut self, name: String);
     fn set_description(&mut self, description: String);
+    fn notes(&self) -> &HashMap<NaiveDate, String>;
+    fn set_note(&mut self, date: NaiveDate, note: String);
+    fn schedule(&self) -> &Schedule;
+    fn set_schedule(&mut self, schedule: Schedule);
+    fn range(&self) -> ActiveRange;
+    fn set_range(&mut self, range: ActiveRange);
+    fn is_active(&self, date: NaiveDate) -> bool;
+    fn pauses(&self) -> &[Pause];
+    fn add_pause(&mut self, pause: Pause);
+    fn remove_pause(&mut self, span: Pause);
+    fn is_paused(&self, date: NaiveDate) -> bool;
+    fn freezes(&self) -> Option<Freezes>;
+    fn set_freezes(&mut self, freezes: Option<Freezes>);
+    fn group(&self) -> Option<&str>;
+    fn set_group(&mut self, group: Option<String>);
+    fn is_due(&self, date: NaiveDate) -> bool;
+    fn days_until_due(&self, today: NaiveDate) -> Option<i64>;
     fn on_event(&mut self, event: Event) -> EventResult;
     fn remaining(&self, date: NaiveDate) -> u32;
     fn required_size(&mut self, _: Vec2) -> Vec2;
@@ -52,6 +363,13 @@ pub trait HabitWrapper: erased_serde::Serialize + Sync + Send {
 
     fn inner_data_ref(&self) -> &InnerData;
     fn inner_data_mut_ref(&mut self) -> &mut InnerData;
+
+    /// All-time stats as of `today`, counting archived months too.
+    fn stats(&self, today: NaiveDate) -> HabitStats {
+        self.stats_between(None, today)
+    }
+
+    fn stats_between(&self, from: Option<NaiveDate>, to: NaiveDate) -> HabitStats;
 }
 
 macro_rules! auto_habit_impl {
@@ -82,24 +400,119 @@ macro_rules! auto_habit_impl {
             fn goal(&self) -> u32 {
                 Habit::goal(self)
             }
+            fn goal_at(&self, date: NaiveDate) -> u32 {
+                Habit::goal_at(self, date)
+            }
+            fn set_goal(
+                &mut self,
+                kind: GoalKind,
+                period: GoalPeriod,
+                since: NaiveDate,
+            ) -> Result<(), String> {
+                Habit::set_goal(self, kind, period, since)
+            }
             fn kind(&self) -> GoalKind {
                 Habit::kind(self)
             }
             fn period(&self) -> GoalPeriod {
                 Habit::period(self)
             }
+            fn period_at(&self, date: NaiveDate) -> GoalPeriod {
+                Habit::period_at(self, date)
+            }
+            fn limit_at(&self, date: NaiveDate) -> Option<u32> {
+                Habit::limit_at(self, date)
+            }
+            fn entry_value(&self, date: NaiveDate) -> Option<String> {
+                Habit::entry_value(self, date)
+            }
+            fn reached_dates(&self) -> Vec<NaiveDate> {
+                Habit::reached_dates(self)
+            }
+            fn stats_between(&self, from: Option<NaiveDate>, to: NaiveDate) -> HabitStats {
+                Habit::stats_between(self, from, to)
+            }
+            fn rating_summary(&self) -> Option<RatingStats> {
+                Habit::rating_summary(self)
+            }
             fn modify(&mut self, date: NaiveDate, event: TrackEvent) {
                 Habit::modify(self, date, event);
             }
+            fn parse_value(&self, input: &str) -> Option<u32> {
+                Habit::parse_value(self, input)
+            }
+            fn format_value(&self, value: u32) -> String {
+                Habit::format_value(self, value)
+            }
+            fn step(&self) -> u32 {
+                Habit::step(self)
+            }
+            fn set_step(&mut self, step: u32) -> Result<(), String> {
+                Habit::set_step(self, step)
+            }
             fn name(&self) -> &str {
                 Habit::name(self)
             }
             fn description(&self) -> &str {
                 Habit::description(self)
             }
+            fn set_name(&mut self, name: String) {
+                Habit::set_name(self, name);
+            }
             fn set_description(&mut self, description: String) {
                 Habit::set_description(self, description);
             }
+            fn notes(&self) -> &HashMap<NaiveDate, String> {
+                Habit::notes(self)
+            }
+            fn set_note(&mut self, date: NaiveDate, note: String) {
+                Habit::set_note(self, date, note);
+            }
+            fn schedule(&self) -> &Schedule {
+                Habit::schedule(self)
+            }
+            fn set_schedule(&mut self, schedule: Schedule) {
+                Habit::set_schedule(self, schedule);
+            }
+            fn range(&self) -> ActiveRange {
+                Habit::range(self)
+            }
+            fn set_range(&mut self, range: ActiveRange) {
+                Habit::set_range(self, range)
+            }
+            fn is_active(&self, date: NaiveDate) -> bool {
+                Habit::is_active(self, date)
+            }
+            fn pauses(&self) -> &[Pause] {
+                Habit::pauses(self)
+            }
+            fn add_pause(&mut self, pause: Pause) {
+                Habit::add_pause(self, pause)
+            }
+            fn remove_pause(&mut self, span: Pause) {
+                Habit::remove_pause(self, span)
+            }
+            fn is_paused(&self, date: NaiveDate) -> bool {
+                Habit::is_paused(self, date)
+            }
+            fn freezes(&self) -> Option<Freezes> {
+                Habit::freezes(self)
+            }
+            fn set_freezes(&mut self, freezes: Option<Freezes>) {
+                Habit::set_freezes(self, freezes)
+            }
+            fn group(&self) -> Option<&str> {
+                Habit::group(self)
+            }
+            fn set_group(&mut self, group: Option<String>) {
+                Habit::set_group(self, group)
+            }
+            fn is_due(&self, date: NaiveDate) -> bool {
+                Habit::is_due(self, date)
+            }
+            fn days_until_due(&self, today: NaiveDate) -> Option<i64> {
+                Habit::days_until_due(self, today)
+            }
             fn inner_data_ref(&self) -> &InnerData {
                 Habit::inner_data_ref(self)
             }
@@ -111,9 +524,10 @@ macro_rules! auto_habit_impl {
                 let mut index = today.with_day(1).unwrap();
 
                 let mut days: Vec<NaiveDate> = Vec::new();
+                let due = Habit::due_dates(self, index, today);
 
                 while index < today {
-                    if Habit::get_by_date(self, index).is_none() {
+                    if due.contains(&index) && Habit::missed_on(self, index) {
                         days.push(index);
                     }
 
@@ -134,4 +548,4 @@ macro_rules! generate_implementations {
     );
 }
 
-generate_implementations!(Count, Bit, Float);
+generate_implementations!(Count, Bit, Float, Addiction, Duration, Cycle, Rating);
@@ -0,0 +1,220 @@
+use chrono::NaiveDate;
+
+use crate::habit::Pause;
+
+/// One [`Pause`] per `VEVENT` in `text`, spanning its `DTSTART` to `DTEND`.
+///
+/// All-day events carry an exclusive `DTEND`, so the pause stops the day
+/// before it; timed events pause the days they touch. An event without a
+/// `DTEND` pauses its start day only. Events whose dates can't be read are
+/// skipped.
+pub fn parse_pauses(text: &str) -> Vec<Pause> {
+    let mut pauses = Vec::new();
+    let mut event: Option<(Option<Value>, Option<Value>)> = None;
+
+    for line in text.lines().map(str::trim_end) {
+        match line {
+            "BEGIN:VEVENT" => event = Some((None, None)),
+            "END:VEVENT" => {
+                if let Some((Some(start), end)) = event.take() {
+                    pauses.push(to_pause(start, end));
+                }
+            }
+            _ => {
+                let Some((start, end)) = event.as_mut() else {
+                    continue;
+                };
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                // `DTSTART;VALUE=DATE:20241224`, `DTEND;TZID=...:20241224T090000`
+                let (name, params) = key.split_once(';').unwrap_or((key, ""));
+                let field = match name {
+                    "DTSTART" => start,
+                    "DTEND" => end,
+                    _ => continue,
+                };
+                *field = parse_value(value, params.contains("VALUE=DATE"));
+            }
+        }
+    }
+    pauses
+}
+
+/// A `DTSTART`/`DTEND` value: the date, and whether it was a whole day.
+struct Value {
+    date: NaiveDate,
+    all_day: bool,
+}
+
+fn parse_value(value: &str, date_param: bool) -> Option<Value> {
+    let date = NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+    Some(Value {
+        date,
+        all_day: date_param || value.len() == 8,
+    })
+}
+
+fn to_pause(start: Value, end: Option<Value>) -> Pause {
+    let last = match end {
+        Some(end) if end.all_day && end.date > start.date => {
+            end.date.pred_opt().unwrap_or(end.date)
+        }
+        Some(end) => end.date,
+        None => start.date,
+    };
+    Pause::new(start.date, last)
+}
+
+/// A calendar with an all-day event per `(date, uid, summary)`, for
+/// overlaying habits on a calendar. Each event's `UID` is made from its date
+/// and `uid`, so importing an export again updates events instead of
+/// doubling them.
+pub fn write_all_day_events<'a>(
+    events: impl IntoIterator<Item = (NaiveDate, &'a str, String)>,
+) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//dijo//dijo export//EN".to_owned(),
+    ];
+    for (date, uid, summary) in events {
+        let day = date.format("%Y%m%d");
+        let next = date.succ_opt().unwrap_or(date).format("%Y%m%d");
+        lines.extend([
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{day}-{}@dijo", uid_part(uid)),
+            format!("DTSTAMP:{day}T000000Z"),
+            format!("DTSTART;VALUE=DATE:{day}"),
+            format!("DTEND;VALUE=DATE:{next}"),
+            format!("SUMMARY:{}", escape_text(&summary)),
+            "TRANSP:TRANSPARENT".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_owned());
+    lines.iter().map(|l| fold(l) + "\r\n").collect()
+}
+
+/// `name` with everything but letters and digits turned into `-`, then a
+/// hash of `name` itself, so that `a, b` and `a; b` don't share a UID.
+fn uid_part(name: &str) -> String {
+    let readable: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{readable}-{:016x}", fnv1a(name))
+}
+
+/// FNV-1a, which unlike the std hasher gives the same hash on every run and
+/// Rust version, keeping UIDs stable between exports.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Escape a `TEXT` value: backslashes, `;`, `,` and line breaks.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Break `line` into lines of at most 75 bytes, each continuation starting
+/// with a space, as calendars expect.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(m: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, m, day).unwrap()
+    }
+
+    #[test]
+    fn all_day_events_end_the_day_before_dtend() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:Christmas break\r\n\
+                   DTSTART;VALUE=DATE:20241223\r\n\
+                   DTEND;VALUE=DATE:20250102\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART;VALUE=DATE:20240501\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        assert_eq!(
+            parse_pauses(ics),
+            vec![
+                Pause::new(d(12, 23), NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
+                Pause::new(d(5, 1), d(5, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn timed_events_cover_the_days_they_touch() {
+        let ics = "BEGIN:VEVENT\n\
+                   DTSTART;TZID=Europe/Berlin:20240610T090000\n\
+                   DTEND;TZID=Europe/Berlin:20240612T170000\n\
+                   END:VEVENT\n";
+        assert_eq!(parse_pauses(ics), vec![Pause::new(d(6, 10), d(6, 12))]);
+    }
+
+    #[test]
+    fn unreadable_events_are_skipped() {
+        let ics = "BEGIN:VEVENT\nDTSTART:someday\nEND:VEVENT\nDTSTART:20240101\n";
+        assert!(parse_pauses(ics).is_empty());
+    }
+
+    #[test]
+    fn events_span_one_whole_day() {
+        let ics = write_all_day_events([(d(12, 31), "gym", "gym".to_owned())]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains(
+            "DTSTAMP:20241231T000000Z\r\n\
+             DTSTART;VALUE=DATE:20241231\r\nDTEND;VALUE=DATE:20250101\r\n"
+        ));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(parse_pauses(&ics), vec![Pause::new(d(12, 31), d(12, 31))]);
+    }
+
+    #[test]
+    fn uids_tell_apart_names_that_read_the_same() {
+        let uid = |name| {
+            let ics = write_all_day_events([(d(1, 1), name, String::new())]);
+            ics.lines()
+                .find(|l| l.starts_with("UID:"))
+                .unwrap()
+                .to_owned()
+        };
+        assert_ne!(uid("read, write"), uid("read; write"));
+        assert_eq!(uid("gym"), uid("gym"));
+        assert!(uid("gym").starts_with("UID:20240101-gym-"));
+    }
+
+    #[test]
+    fn summaries_are_escaped_and_folded() {
+        let summary = format!("read, write; {}", "x".repeat(80));
+        let ics = write_all_day_events([(d(1, 1), "read, write", summary)]);
+        assert!(ics.contains("UID:20240101-read--write-"));
+        assert!(ics.contains("SUMMARY:read\\, write\\; xxx"));
+        assert!(ics.lines().all(|l| l.trim_end().len() <= 75));
+        assert!(ics.contains("\r\n xxx"));
+    }
+}
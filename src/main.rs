@@ -1,15 +1,17 @@
 mod app;
 mod command;
 mod habit;
+mod ics;
 mod stats;
 mod theme;
 mod utils;
 mod views;
 
-use crate::app::App;
-use crate::command::open_command_window;
+use crate::app::{App, ExportFormat, StatsFormat, StatusFormat};
+use crate::command::{open_command_window, parse_date};
 use crate::utils::{AppConfig, load_configuration_file};
 
+use chrono::NaiveDate;
 use clap::{Arg, Command as ClapApp};
 
 use cursive::views::{LinearLayout, NamedView};
@@ -49,6 +51,126 @@ fn main() {
                 .help("missings habits")
                 .conflicts_with("list"),
         )
+        .arg(
+            Arg::new("script")
+                .long("script")
+                .short('s')
+                .action(clap::ArgAction::Set)
+                .value_name("FILE")
+                .help("run the command-mode lines in FILE (- for stdin), then save")
+                .conflicts_with_all(["list", "missing"]),
+        )
+        .subcommand(
+            ClapApp::new("exec")
+                .about("run a command-mode command without opening dijo")
+                .arg(
+                    Arg::new("command")
+                        .required(true)
+                        .num_args(1..)
+                        .allow_hyphen_values(true)
+                        .value_name("COMMAND"),
+                ),
+        )
+        .subcommand(
+            ClapApp::new("export")
+                .about("write every habit's days, archived months included, for other tools")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .value_name("FORMAT")
+                        .value_parser(["csv", "jsonl", "ics"])
+                        .default_value("csv")
+                        .help("ics gives an all-day event per day a goal was reached"),
+                )
+                .arg(
+                    Arg::new("habit")
+                        .long("habit")
+                        .value_name("HABIT")
+                        .help("export only this habit"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("YYYY-MM-DD")
+                        .help("export from this day on"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("YYYY-MM-DD")
+                        .help("export up to this day"),
+                ),
+        )
+        .subcommand(
+            ClapApp::new("rename")
+                .about("rename a habit, including its archived months")
+                .arg(Arg::new("old").required(true).value_name("HABIT"))
+                .arg(Arg::new("new").required(true).value_name("NEW_NAME")),
+        )
+        .subcommand(
+            ClapApp::new("stats")
+                .about("print totals, streaks and completion rates of one habit or all of them")
+                .arg(Arg::new("habit").value_name("HABIT"))
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("YYYY-MM-DD")
+                        .help("count from this day on, instead of from the start of the habit's active range"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("YYYY-MM-DD")
+                        .default_value("today")
+                        .help("count up to this day"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("csv"),
+                )
+                .arg(Arg::new("csv").long("csv").action(clap::ArgAction::SetTrue)),
+        )
+        .subcommand(
+            ClapApp::new("status")
+                .about("print today's progress, for shell prompts and status bars")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .short('f')
+                        .value_name("FORMAT")
+                        .value_parser(["plain", "json", "waybar", "i3blocks"])
+                        .default_value("plain"),
+                )
+                .arg(
+                    Arg::new("template")
+                        .long("template")
+                        .short('t')
+                        .value_name("TEMPLATE")
+                        .help("e.g. \"{completed}/{total} {todo}\"; a habit's name in braces gives what is left of it"),
+                ),
+        )
+        .subcommand(
+            ClapApp::new("track")
+                .about("record a habit without opening dijo")
+                .arg(Arg::new("habit").required(true).value_name("HABIT"))
+                .arg(
+                    Arg::new("change")
+                        .value_name("+N|-N|=VALUE")
+                        .allow_hyphen_values(true)
+                        .default_value("+1")
+                        .help("step the day up or down N times, or set its value"),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .value_name("YYYY-MM-DD|yesterday")
+                        .default_value("today")
+                        .help("the day to record"),
+                ),
+        )
         .get_matches();
 
     let load_state = || {
@@ -58,7 +180,91 @@ fn main() {
         })
     };
 
-    if matches.get_flag("list") {
+    if let Some(("exec", args)) = matches.subcommand() {
+        // `dijo exec add gym 3` and `dijo exec "add gym 3"` are the same
+        let words: Vec<&str> = args
+            .get_many::<String>("command")
+            .unwrap()
+            .map(String::as_str)
+            .collect();
+        let line = words.join(" ");
+        if let Err(e) = run_commands(&mut load_state(), "exec", [line.as_str()]) {
+            eprintln!("dijo: {e}");
+            std::process::exit(1);
+        }
+    } else if let Some(path) = matches.get_one::<String>("script") {
+        let script = if path == "-" {
+            std::io::read_to_string(std::io::stdin())
+        } else {
+            std::fs::read_to_string(path)
+        };
+        let script = script.unwrap_or_else(|e| {
+            eprintln!("dijo: could not read `{path}`: {e}");
+            std::process::exit(2);
+        });
+        if let Err(e) = run_commands(&mut load_state(), path, script.lines()) {
+            eprintln!("dijo: {e}");
+            std::process::exit(1);
+        }
+    } else if let Some(("rename", args)) = matches.subcommand() {
+        let old = args.get_one::<String>("old").unwrap();
+        let new = args.get_one::<String>("new").unwrap();
+        let mut app = load_state();
+        if let Err(e) = app.rename_habit(old, new).and_then(|_| app.save_state()) {
+            eprintln!("dijo: {e}");
+            std::process::exit(1);
+        }
+    } else if let Some(("export", args)) = matches.subcommand() {
+        let format: ExportFormat = args.get_one::<String>("format").unwrap().parse().unwrap();
+        let habit = args.get_one::<String>("habit").map(String::as_str);
+        let (from, to) = date_span(args);
+        match load_state().export(&utils::load_archived_habits(), habit, from, to) {
+            Ok(export) => print!("{}", export.render(format)),
+            Err(e) => {
+                eprintln!("dijo: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(("stats", args)) = matches.subcommand() {
+        let (from, to) = date_span(args);
+        let to = to.unwrap();
+        let format = if args.get_flag("json") {
+            StatsFormat::Json
+        } else if args.get_flag("csv") {
+            StatsFormat::Csv
+        } else {
+            StatsFormat::Table
+        };
+        let habit = args.get_one::<String>("habit").map(String::as_str);
+        match load_state().stats_report(habit, from, to) {
+            Ok(report) => println!("{}", report.render(format)),
+            Err(e) => {
+                eprintln!("dijo: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(("status", args)) = matches.subcommand() {
+        let format: StatusFormat = args.get_one::<String>("format").unwrap().parse().unwrap();
+        let template = args.get_one::<String>("template").map(String::as_str);
+        let today = chrono::Local::now().date_naive();
+        println!("{}", load_state().summary(today).render(format, template));
+    } else if let Some(("track", args)) = matches.subcommand() {
+        let habit = args.get_one::<String>("habit").unwrap();
+        let change = args.get_one::<String>("change").unwrap();
+        let raw_date = args.get_one::<String>("date").unwrap();
+        let Some(date) = parse_date(raw_date) else {
+            eprintln!("dijo: `{raw_date}` is not a date, use YYYY-MM-DD, today or yesterday");
+            std::process::exit(1);
+        };
+        let mut app = load_state();
+        if let Err(e) = app
+            .track_habit(habit, change, date)
+            .and_then(|_| app.save_state())
+        {
+            eprintln!("dijo: {e}");
+            std::process::exit(1);
+        }
+    } else if matches.get_flag("list") {
         for h in load_state().list_habits() {
             println!("{h}");
         }
@@ -88,3 +294,46 @@ fn main() {
         }
     }
 }
+
+/// The `--from` and `--to` dates of a subcommand, either of which may be
+/// left out. Exits when one can't be read or they are the wrong way round.
+fn date_span(args: &clap::ArgMatches) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let date = |id: &str| {
+        let raw = args.get_one::<String>(id)?;
+        let date = parse_date(raw).unwrap_or_else(|| {
+            eprintln!("dijo: `{raw}` is not a date, use YYYY-MM-DD, today or yesterday");
+            std::process::exit(1);
+        });
+        Some(date)
+    };
+    let (from, to) = (date("from"), date("to"));
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        eprintln!("dijo: --from is after --to");
+        std::process::exit(1);
+    }
+    (from, to)
+}
+
+/// Run command-mode lines against `app` without opening the grid, printing
+/// whatever they report, then save. Blank lines and `#` comments are skipped.
+/// The first line that fails stops the run, and nothing is saved.
+fn run_commands<'a>(
+    app: &mut App,
+    source: &str,
+    lines: impl IntoIterator<Item = &'a str>,
+) -> Result<(), String> {
+    for (n, line) in lines.into_iter().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match app.exec(line) {
+            Ok(Some(report)) => println!("{report}"),
+            Ok(None) => {}
+            Err(e) => return Err(format!("{source}:{}: {e}", n + 1)),
+        }
+    }
+    app.save_state()
+}
@@ -1,16 +1,17 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
 
-use crate::habit::GoalPeriod;
+use crate::habit::{Freezes, GoalPeriod};
 use crate::utils::week_bounds;
 
 /// Aggregate stats for a single habit, derived purely from the set of dates on
 /// which it reached its goal. The unit of counting is the habit's goal period:
-/// days for a daily goal, whole weeks for a weekly one.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// days for a daily goal, whole weeks or months for a weekly or monthly one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
 pub struct HabitStats {
-    /// Total number of periods (days/weeks) the goal was reached.
+    /// Total number of periods (days/weeks/months) the goal was reached.
     pub total: u32,
     /// Length of the streak ending in the current period (counting it, or the
     /// previous one if the current period is not done yet).
@@ -19,17 +20,116 @@ pub struct HabitStats {
     pub longest_streak: u32,
     /// Reached periods as a percentage of the span since the first reached one.
     pub completion_rate: u32,
+    /// Freezes still unused in the current allowance period, for a habit
+    /// that has an allowance.
+    pub freezes_left: Option<u32>,
+}
+
+/// Compute [`HabitStats`] for a habit due `every` N days after its last
+/// completion. Streaks count on-time completions, each within `every` days of
+/// the one before, rather than consecutive days; the current streak lapses
+/// once the habit is overdue. The completion rate is the share of `every`-day
+/// intervals since `since`, or else the first completion, that had one. Days
+/// for which `is_paused` holds do not count toward any gap.
+pub fn interval_stats(
+    reached: &[NaiveDate],
+    today: NaiveDate,
+    every: u32,
+    since: Option<NaiveDate>,
+    is_paused: impl Fn(NaiveDate) -> bool,
+) -> HabitStats {
+    let every = every.max(1) as i64;
+    // unpaused days after `from` up to and including `to`
+    let gap = |from: NaiveDate, to: NaiveDate| -> i64 {
+        from.iter_days()
+            .skip(1)
+            .take_while(|&d| d <= to)
+            .filter(|&d| !is_paused(d))
+            .count() as i64
+    };
+    let mut days = reached.to_vec();
+    days.sort_unstable();
+    days.dedup();
+
+    let (Some(&first), Some(&last)) = (days.first(), days.last()) else {
+        return HabitStats::default();
+    };
+
+    let mut longest = 0u32;
+    let mut run = 0u32;
+    let mut prev: Option<NaiveDate> = None;
+    for &d in &days {
+        run = match prev {
+            Some(p) if gap(p, d) <= every => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        prev = Some(d);
+    }
+    // `run` now ends at the last completion
+    let current = if gap(last, today) <= every { run } else { 0 };
+
+    let total = days.len() as u32;
+    let span = (gap(since.unwrap_or(first), today) / every + 1).max(1) as u32;
+    let completion_rate = total.min(span) * 100 / span;
+
+    HabitStats {
+        total,
+        current_streak: current,
+        longest_streak: longest,
+        completion_rate,
+        freezes_left: None,
+    }
+}
+
+/// Summary of a `Rating` habit's values: their mean, how often each value was
+/// given, and the mean per weekday.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RatingStats {
+    /// Mean of every rating, or `None` when nothing was rated.
+    pub average: Option<f64>,
+    /// `(value, times given)` for every value of the scale, lowest first.
+    pub distribution: Vec<(u32, u32)>,
+    /// Mean rating for each weekday, Monday first.
+    pub weekday_averages: [Option<f64>; 7],
+}
+
+/// Compute [`RatingStats`] from `(date, value)` pairs on the `min..=max` scale.
+/// Values outside the scale still count toward the averages but have no
+/// histogram bucket.
+pub fn rating_stats(values: &[(NaiveDate, u32)], min: u32, max: u32) -> RatingStats {
+    let mean = |sum: u32, n: u32| (n > 0).then(|| sum as f64 / n as f64);
+
+    let mut distribution: Vec<(u32, u32)> = (min..=max).map(|v| (v, 0)).collect();
+    let mut weekdays = [(0u32, 0u32); 7];
+    let mut sum = 0;
+    for &(date, value) in values {
+        sum += value;
+        if let Some(bucket) = distribution.iter_mut().find(|(v, _)| *v == value) {
+            bucket.1 += 1;
+        }
+        let day = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+        day.0 += value;
+        day.1 += 1;
+    }
+
+    RatingStats {
+        average: mean(sum, values.len() as u32),
+        distribution,
+        weekday_averages: weekdays.map(|(sum, n)| mean(sum, n)),
+    }
 }
 
 /// Map a date onto a monotonic index for its goal period, so that consecutive
 /// periods differ by exactly 1. Daily uses the day number; weekly uses the
-/// week's Monday collapsed to a week count.
+/// week's Monday collapsed to a week count; monthly counts months.
 fn period_index(date: NaiveDate, period: GoalPeriod) -> i64 {
     match period {
         GoalPeriod::Daily => date.num_days_from_ce() as i64,
         // Adding 7 days always bumps the quotient by exactly 1, so successive
         // Mondays land on successive indices.
         GoalPeriod::Weekly => week_bounds(date).0.num_days_from_ce() as i64 / 7,
+        GoalPeriod::Monthly => date.year() as i64 * 12 + date.month0() as i64,
     }
 }
 
@@ -37,61 +137,104 @@ fn period_index(date: NaiveDate, period: GoalPeriod) -> i64 {
 ///
 /// `reached` need not be sorted or deduplicated; days that fall in the same
 /// period collapse into one. `today` anchors the current streak and the
-/// completion-rate span, and `period` sets the counting unit.
-pub fn habit_stats(reached: &[NaiveDate], today: NaiveDate, period: GoalPeriod) -> HabitStats {
-    let mut idx: Vec<i64> = reached.iter().map(|&d| period_index(d, period)).collect();
-    idx.sort_unstable();
-    idx.dedup();
+/// completion-rate span, which begins at `since`, or else at the first reached
+/// day, and `period` sets the counting unit. A period with no
+/// day for which `is_due` holds (e.g. outside a weekday schedule) is skipped:
+/// it neither breaks a streak nor counts toward the completion rate, unless it
+/// was reached anyway.
+///
+/// With `freezes`, up to `count` missed periods in each allowance period are
+/// bridged: they keep a running streak alive without adding to it.
+pub fn habit_stats(
+    reached: &[NaiveDate],
+    today: NaiveDate,
+    period: GoalPeriod,
+    freezes: Option<Freezes>,
+    since: Option<NaiveDate>,
+    is_due: impl Fn(NaiveDate) -> bool,
+) -> HabitStats {
+    let reached_set: HashSet<i64> = reached.iter().map(|&d| period_index(d, period)).collect();
+    let total = reached_set.len() as u32;
 
-    let total = idx.len() as u32;
+    let Some(first) = since.or_else(|| reached.iter().min().copied()) else {
+        return HabitStats {
+            freezes_left: freezes.map(|f| f.count),
+            ..HabitStats::default()
+        };
+    };
+    let last = reached.iter().max().copied().unwrap_or(first);
 
-    // longest run of consecutive reached periods
-    let mut longest = 0u32;
-    let mut run = 0u32;
-    let mut prev: Option<i64> = None;
-    for &i in &idx {
-        run = match prev {
-            Some(p) if p + 1 == i => run + 1,
-            _ => 1,
+    // every period from the start of the span on, as (index, first day,
+    // reached), minus the ones that were never due and not reached either
+    let mut periods: Vec<(i64, NaiveDate, bool)> = Vec::new();
+    let mut keep = |(i, start, due): (i64, NaiveDate, bool)| {
+        let hit = reached_set.contains(&i);
+        if due || hit {
+            periods.push((i, start, hit));
+        }
+    };
+    let mut open = (period_index(first, period), first, false);
+    let mut day = first;
+    while day <= today.max(last) {
+        let i = period_index(day, period);
+        if i != open.0 {
+            keep(open);
+            open = (i, day, false);
+        }
+        open.2 |= is_due(day);
+        day = match day.succ_opt() {
+            Some(d) => d,
+            None => break,
         };
-        longest = longest.max(run);
-        prev = Some(i);
     }
+    keep(open);
 
-    let reached_set: HashSet<i64> = idx.iter().copied().collect();
-
-    // current streak: count back from the current period, tolerating a still-
-    // open one (start from the previous period if this one isn't done yet)
+    // walk the periods in order, tracking the running streak; a still-open
+    // current period neither extends nor breaks it
     let today_idx = period_index(today, period);
+    let mut used: HashMap<i64, u32> = HashMap::new();
+    let mut longest = 0u32;
+    let mut run = 0u32;
     let mut current = 0u32;
-    let mut cur = if reached_set.contains(&today_idx) {
-        today_idx
-    } else {
-        today_idx - 1
-    };
-    while reached_set.contains(&cur) {
-        current += 1;
-        cur -= 1;
-    }
-
-    // completion rate over the span since the first reached period
-    let completion_rate = match idx.first() {
-        Some(&first) => {
-            let span = today_idx - first + 1;
-            if span > 0 {
-                (total as i64 * 100 / span) as u32
-            } else {
-                0
-            }
+    for &(i, start, hit) in &periods {
+        if i == today_idx && !hit {
+            continue;
         }
-        None => 0,
-    };
+        if hit {
+            run += 1;
+        } else if let Some(f) = freezes
+            && run > 0
+            && used.get(&period_index(start, f.per)).copied().unwrap_or(0) < f.count
+        {
+            *used.entry(period_index(start, f.per)).or_default() += 1;
+        } else {
+            run = 0;
+        }
+        longest = longest.max(run);
+        if i <= today_idx {
+            current = run;
+        }
+    }
+
+    // completion rate over the span since its first period
+    let span = periods.iter().filter(|(i, ..)| *i <= today_idx).count() as u32;
+    let done = periods
+        .iter()
+        .filter(|&&(i, _, hit)| hit && i <= today_idx)
+        .count() as u32;
+    let completion_rate = (done * 100).checked_div(span).unwrap_or(0);
+
+    let freezes_left = freezes.map(|f| {
+        let spent = used.get(&period_index(today, f.per)).copied().unwrap_or(0);
+        f.count.saturating_sub(spent)
+    });
 
     HabitStats {
         total,
         current_streak: current,
         longest_streak: longest,
         completion_rate,
+        freezes_left,
     }
 }
 
@@ -104,11 +247,15 @@ mod tests {
     }
 
     fn daily(reached: &[NaiveDate], today: NaiveDate) -> HabitStats {
-        habit_stats(reached, today, GoalPeriod::Daily)
+        habit_stats(reached, today, GoalPeriod::Daily, None, None, |_| true)
     }
 
     fn weekly(reached: &[NaiveDate], today: NaiveDate) -> HabitStats {
-        habit_stats(reached, today, GoalPeriod::Weekly)
+        habit_stats(reached, today, GoalPeriod::Weekly, None, None, |_| true)
+    }
+
+    fn monthly(reached: &[NaiveDate], today: NaiveDate) -> HabitStats {
+        habit_stats(reached, today, GoalPeriod::Monthly, None, None, |_| true)
     }
 
     #[test]
@@ -225,4 +372,258 @@ mod tests {
         let dates = [d(2024, 1, 1), d(2024, 1, 15)];
         assert_eq!(weekly(&dates, d(2024, 1, 28)).completion_rate, 50);
     }
+
+    // ---- pauses ----
+
+    #[test]
+    fn paused_days_do_not_break_a_streak() {
+        // Jan 1..=3 done, Jan 4..=6 paused, Jan 7..=8 done
+        let paused = |day: NaiveDate| (d(2024, 1, 4)..=d(2024, 1, 6)).contains(&day);
+        let dates = [
+            d(2024, 1, 1),
+            d(2024, 1, 2),
+            d(2024, 1, 3),
+            d(2024, 1, 7),
+            d(2024, 1, 8),
+        ];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 8),
+            GoalPeriod::Daily,
+            None,
+            None,
+            |day| !paused(day),
+        );
+        assert_eq!(s.current_streak, 5);
+        assert_eq!(s.longest_streak, 5);
+        assert_eq!(s.completion_rate, 100);
+    }
+
+    // ---- freezes ----
+
+    fn two_a_week() -> Option<Freezes> {
+        Some(Freezes {
+            count: 2,
+            per: GoalPeriod::Weekly,
+        })
+    }
+
+    #[test]
+    fn freezes_bridge_missed_days_within_the_allowance() {
+        // Mon 1 .. Sun 7 Jan 2024, missing Wed 3 and Fri 5
+        let dates = [
+            d(2024, 1, 1),
+            d(2024, 1, 2),
+            d(2024, 1, 4),
+            d(2024, 1, 6),
+            d(2024, 1, 7),
+        ];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 7),
+            GoalPeriod::Daily,
+            two_a_week(),
+            None,
+            |_| true,
+        );
+        assert_eq!(s.current_streak, 5, "frozen days bridge but do not count");
+        assert_eq!(s.longest_streak, 5);
+        assert_eq!(s.freezes_left, Some(0));
+        assert_eq!(s.completion_rate, 71, "freezes do not change the rate");
+    }
+
+    #[test]
+    fn a_miss_beyond_the_allowance_breaks_the_streak() {
+        // three misses in one week: 2, 3 and 4 Jan
+        let dates = [d(2024, 1, 1), d(2024, 1, 5), d(2024, 1, 6)];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 6),
+            GoalPeriod::Daily,
+            two_a_week(),
+            None,
+            |_| true,
+        );
+        assert_eq!(s.current_streak, 2);
+        assert_eq!(s.longest_streak, 2);
+    }
+
+    #[test]
+    fn the_allowance_renews_each_period() {
+        // misses on Sun 7 and Mon 8 Jan fall in different weeks
+        let one_a_week = Some(Freezes {
+            count: 1,
+            per: GoalPeriod::Weekly,
+        });
+        let dates = [d(2024, 1, 5), d(2024, 1, 6), d(2024, 1, 9)];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 9),
+            GoalPeriod::Daily,
+            one_a_week,
+            None,
+            |_| true,
+        );
+        assert_eq!(s.current_streak, 3);
+        assert_eq!(s.freezes_left, Some(0));
+        let s = habit_stats(
+            &[],
+            d(2024, 1, 9),
+            GoalPeriod::Daily,
+            one_a_week,
+            None,
+            |_| true,
+        );
+        assert_eq!(s.freezes_left, Some(1), "nothing used yet");
+    }
+
+    // ---- monthly counting ----
+
+    #[test]
+    fn monthly_streak_runs_across_the_year_boundary() {
+        let dates = [d(2023, 11, 5), d(2023, 12, 20), d(2024, 1, 3)];
+        let s = monthly(&dates, d(2024, 2, 10));
+        assert_eq!(s.total, 3);
+        assert_eq!(s.longest_streak, 3);
+        assert_eq!(s.current_streak, 3, "February is still open");
+    }
+
+    #[test]
+    fn monthly_completion_rate_is_over_months() {
+        // reached Jan and Mar of Jan..=Apr -> 2/4 = 50%
+        let dates = [d(2024, 1, 31), d(2024, 3, 1)];
+        let s = monthly(&dates, d(2024, 4, 30));
+        assert_eq!(s.completion_rate, 50);
+        assert_eq!(s.current_streak, 1, "April is open, March was reached");
+    }
+
+    // ---- schedules ----
+
+    fn weekdays_only(date: NaiveDate) -> bool {
+        date.weekday().num_days_from_monday() < 5
+    }
+
+    #[test]
+    fn unscheduled_days_do_not_break_a_streak() {
+        // Thu 2024-01-04, Fri 05, (weekend off), Mon 08, Tue 09
+        let dates = [d(2024, 1, 4), d(2024, 1, 5), d(2024, 1, 8), d(2024, 1, 9)];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 9),
+            GoalPeriod::Daily,
+            None,
+            None,
+            weekdays_only,
+        );
+        assert_eq!(s.current_streak, 4);
+        assert_eq!(s.longest_streak, 4);
+        assert_eq!(s.completion_rate, 100, "the weekend is not in the span");
+    }
+
+    #[test]
+    fn an_open_unscheduled_today_keeps_the_streak() {
+        // Fri 2024-01-05 done, today is Sat 06 (off)
+        let dates = [d(2024, 1, 4), d(2024, 1, 5)];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 6),
+            GoalPeriod::Daily,
+            None,
+            None,
+            weekdays_only,
+        );
+        assert_eq!(s.current_streak, 2);
+    }
+
+    #[test]
+    fn a_missed_scheduled_day_still_breaks_the_streak() {
+        // Thu 04 done, Fri 05 missed, Mon 08 done
+        let dates = [d(2024, 1, 4), d(2024, 1, 8)];
+        let s = habit_stats(
+            &dates,
+            d(2024, 1, 8),
+            GoalPeriod::Daily,
+            None,
+            None,
+            weekdays_only,
+        );
+        assert_eq!(s.current_streak, 1);
+        assert_eq!(s.completion_rate, 66);
+    }
+
+    // ---- intervals ----
+
+    #[test]
+    fn interval_streak_counts_on_time_completions() {
+        // every 3 days: 1 -> 4 -> 6 on time, 12 late, 14 on time
+        let dates = [
+            d(2024, 1, 1),
+            d(2024, 1, 4),
+            d(2024, 1, 6),
+            d(2024, 1, 12),
+            d(2024, 1, 14),
+        ];
+        let s = interval_stats(&dates, d(2024, 1, 16), 3, None, |_| false);
+        assert_eq!(s.total, 5);
+        assert_eq!(s.longest_streak, 3);
+        assert_eq!(s.current_streak, 2);
+    }
+
+    #[test]
+    fn interval_streak_lapses_once_overdue() {
+        let dates = [d(2024, 1, 1), d(2024, 1, 4)];
+        assert_eq!(
+            interval_stats(&dates, d(2024, 1, 7), 3, None, |_| false).current_streak,
+            2
+        );
+        assert_eq!(
+            interval_stats(&dates, d(2024, 1, 8), 3, None, |_| false).current_streak,
+            0
+        );
+    }
+
+    #[test]
+    fn interval_completion_rate_is_over_intervals() {
+        // Jan 1..=12 is four 3-day intervals, two of which had a completion
+        let dates = [d(2024, 1, 1), d(2024, 1, 2)];
+        assert_eq!(
+            interval_stats(&dates, d(2024, 1, 12), 3, None, |_| false).completion_rate,
+            50
+        );
+    }
+
+    #[test]
+    fn interval_gaps_skip_paused_days() {
+        // every 3 days with Jan 3..=7 paused: Jan 1 -> Jan 9 is 3 active days
+        let paused = |day: NaiveDate| (d(2024, 1, 3)..=d(2024, 1, 7)).contains(&day);
+        let dates = [d(2024, 1, 1), d(2024, 1, 9)];
+        let s = interval_stats(&dates, d(2024, 1, 10), 3, None, paused);
+        assert_eq!(s.current_streak, 2);
+        assert_eq!(
+            interval_stats(&dates, d(2024, 1, 10), 3, None, |_| false).current_streak,
+            1
+        );
+    }
+
+    // ---- ratings ----
+
+    #[test]
+    fn rating_stats_of_nothing_has_no_average() {
+        let r = rating_stats(&[], 1, 3);
+        assert_eq!(r.average, None);
+        assert_eq!(r.distribution, vec![(1, 0), (2, 0), (3, 0)]);
+        assert_eq!(r.weekday_averages, [None; 7]);
+    }
+
+    #[test]
+    fn rating_stats_averages_and_buckets_values() {
+        // 2024-01-01 and 2024-01-08 are Mondays, 2024-01-02 a Tuesday
+        let values = [(d(2024, 1, 1), 2), (d(2024, 1, 8), 4), (d(2024, 1, 2), 5)];
+        let r = rating_stats(&values, 1, 5);
+        assert_eq!(r.average, Some(11.0 / 3.0));
+        assert_eq!(r.distribution, vec![(1, 0), (2, 1), (3, 0), (4, 1), (5, 1)]);
+        assert_eq!(r.weekday_averages[0], Some(3.0));
+        assert_eq!(r.weekday_averages[1], Some(5.0));
+        assert_eq!(r.weekday_averages[2], None);
+    }
 }
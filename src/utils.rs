@@ -1,12 +1,14 @@
-use chrono::{Datelike, Days, NaiveDate};
+use chrono::{Datelike, Days, Months, NaiveDate};
 use cursive::theme::{BaseColor, Color};
-use directories::ProjectDirs;
+use directories::{BaseDirs, ProjectDirs};
 use serde::{Deserialize, Serialize};
 
+use crate::habit::{GoalHistory, GoalPeriod};
+
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const VIEW_WIDTH: usize = 30;
 pub const VIEW_HEIGHT: usize = 10;
@@ -21,6 +23,55 @@ pub fn week_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
     (monday, sunday)
 }
 
+/// The first and last day of the calendar month that contains `date`. Used by
+/// monthly-goal habits to aggregate a month's entries.
+pub fn month_bounds(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = date.with_day(1).unwrap_or(date);
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(date);
+    (first, last)
+}
+
+/// The span of days sharing `date`'s goal period: the day itself, its Mon–Sun
+/// week or its calendar month.
+pub fn period_bounds(date: NaiveDate, period: GoalPeriod) -> (NaiveDate, NaiveDate) {
+    match period {
+        GoalPeriod::Daily => (date, date),
+        GoalPeriod::Weekly => week_bounds(date),
+        GoalPeriod::Monthly => month_bounds(date),
+    }
+}
+
+/// Sum `value` over every day of the goal period containing `date`. Shared by
+/// the weekly- and monthly-goal habits so they all aggregate a period the same
+/// way.
+pub fn period_total(date: NaiveDate, period: GoalPeriod, value: impl Fn(NaiveDate) -> u32) -> u32 {
+    let (first, last) = period_bounds(date, period);
+    let mut day = first;
+    let mut total = 0;
+    while day <= last {
+        total += value(day);
+        day = match day.checked_add_days(Days::new(1)) {
+            Some(d) => d,
+            None => break,
+        };
+    }
+    total
+}
+
+/// Describe `days` until an every-N-days habit is next due, as returned by
+/// `Habit::days_until_due`.
+pub fn due_label(days: i64) -> String {
+    let plural = |n: i64| if n == 1 { "" } else { "s" };
+    match days {
+        0 => "due today".to_owned(),
+        d if d > 0 => format!("due in {d} day{}", plural(d)),
+        d => format!("overdue by {} day{}", -d, plural(-d)),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Characters {
     #[serde(default = "base_char")]
@@ -31,12 +82,18 @@ pub struct Characters {
     pub future_chr: char,
     #[serde(default = "base_char")]
     pub missing_chr: char,
+    #[serde(default = "paused_char")]
+    pub paused_chr: char,
 }
 
 fn base_char() -> char {
     '·'
 }
 
+fn paused_char() -> char {
+    '~'
+}
+
 impl Default for Characters {
     fn default() -> Self {
         Characters {
@@ -44,6 +101,7 @@ impl Default for Characters {
             false_chr: '-',
             future_chr: '.',
             missing_chr: '?',
+            paused_chr: paused_char(),
         }
     }
 }
@@ -97,6 +155,41 @@ impl AppConfig {
     pub fn inactive_color(&self) -> Color {
         Color::parse(&self.colors.inactive).unwrap_or(Color::Light(BaseColor::Black))
     }
+    /// The reached colour as `#RRGGBB`, for status bars drawn outside the
+    /// terminal; `None` for the terminal's own default colour.
+    pub fn reached_hex(&self) -> Option<String> {
+        hex(self.reached_color())
+    }
+}
+
+/// `color` as `#RRGGBB`, taking the usual xterm values for the named colours.
+fn hex(color: Color) -> Option<String> {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::RgbLowRes(r, g, b) => (r * 51, g * 51, b * 51),
+        Color::Dark(base) => match base {
+            BaseColor::Black => (0x00, 0x00, 0x00),
+            BaseColor::Red => (0xCD, 0x00, 0x00),
+            BaseColor::Green => (0x00, 0xCD, 0x00),
+            BaseColor::Yellow => (0xCD, 0xCD, 0x00),
+            BaseColor::Blue => (0x00, 0x00, 0xEE),
+            BaseColor::Magenta => (0xCD, 0x00, 0xCD),
+            BaseColor::Cyan => (0x00, 0xCD, 0xCD),
+            BaseColor::White => (0xE5, 0xE5, 0xE5),
+        },
+        Color::Light(base) => match base {
+            BaseColor::Black => (0x7F, 0x7F, 0x7F),
+            BaseColor::Red => (0xFF, 0x00, 0x00),
+            BaseColor::Green => (0x00, 0xFF, 0x00),
+            BaseColor::Yellow => (0xFF, 0xFF, 0x00),
+            BaseColor::Blue => (0x5C, 0x5C, 0xFF),
+            BaseColor::Magenta => (0xFF, 0x00, 0xFF),
+            BaseColor::Cyan => (0x00, 0xFF, 0xFF),
+            BaseColor::White => (0xFF, 0xFF, 0xFF),
+        },
+        Color::TerminalDefault => return None,
+    };
+    Some(format!("#{r:02X}{g:02X}{b:02X}"))
 }
 
 /// Load the user config, falling back to defaults on any problem.
@@ -139,6 +232,15 @@ fn project_dirs() -> Result<ProjectDirs, String> {
         .ok_or_else(|| "could not determine a home directory".to_string())
 }
 
+/// Expand a leading `~/` in a path typed on the command line to the home
+/// directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
 pub fn config_file() -> Result<PathBuf, String> {
     let proj_dirs = project_dirs()?;
     let dir = proj_dirs.config_dir();
@@ -161,16 +263,33 @@ pub fn archive_dir() -> Result<PathBuf, String> {
 }
 
 /// Scan archive files and return reached-goal dates grouped by habit name.
-/// Archive files are `{month}_{year}.json` containing arrays of serialized habits.
-/// Each habit has "name", "goal", and "stats" fields.
 pub fn load_archived_reached_goals() -> HashMap<String, HashSet<NaiveDate>> {
     let mut result: HashMap<String, HashSet<NaiveDate>> = HashMap::new();
-    let archive_path = match archive_dir() {
-        Ok(p) => p,
-        Err(_) => return result,
-    };
+    for habit in load_archived_habits() {
+        let name = match habit.get("name").and_then(|n| n.as_str()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
+        let dates = result.entry(name).or_default();
+        dates.extend(archived_reached_dates(&habit));
+    }
+    result
+}
+
+/// Every habit in every archive file, one per habit and month, as stored.
+/// Archive files are `{month}_{year}.json` containing arrays of serialized
+/// habits, each with "name", "goal", and "stats" fields.
+pub fn load_archived_habits() -> Vec<serde_json::Value> {
+    match archive_dir() {
+        Ok(archive_path) => load_archived_habits_in(&archive_path),
+        Err(_) => Vec::new(),
+    }
+}
 
-    let entries = match fs::read_dir(&archive_path) {
+/// `load_archived_habits` from the archive files in `archive_path`.
+pub fn load_archived_habits_in(archive_path: &Path) -> Vec<serde_json::Value> {
+    let mut result = Vec::new();
+    let entries = match fs::read_dir(archive_path) {
         Ok(e) => e,
         Err(_) => return result,
     };
@@ -190,39 +309,78 @@ pub fn load_archived_reached_goals() -> HashMap<String, HashSet<NaiveDate>> {
             continue;
         }
 
-        let habits: Vec<serde_json::Value> = match serde_json::from_str(&contents) {
-            Ok(v) => v,
+        match serde_json::from_str::<Vec<serde_json::Value>>(&contents) {
+            Ok(habits) => result.extend(habits),
             Err(_) => continue,
-        };
+        }
+    }
 
-        for habit in habits {
-            let name = match habit.get("name").and_then(|n| n.as_str()) {
-                Some(n) => n.to_string(),
-                None => continue,
-            };
+    result
+}
 
-            let dates = result.entry(name).or_default();
-            dates.extend(archived_reached_dates(&habit));
+/// Rename habit `old` to `new` in every archive file in `archive_path`, so
+/// its archived months keep attaching to it. Returns how many files were
+/// rewritten.
+pub fn rename_archived(archive_path: &Path, old: &str, new: &str) -> Result<usize, String> {
+    let entries =
+        fs::read_dir(archive_path).map_err(|e| format!("could not read archive dir: {e}"))?;
+
+    let mut rewritten = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
         }
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(mut habits) = serde_json::from_str::<Vec<serde_json::Value>>(&contents) else {
+            continue;
+        };
+        if !rename_in(&mut habits, old, new) {
+            continue;
+        }
+
+        let mut o = serde_json::json!(habits);
+        o.sort_all_objects();
+        let j = serde_json::to_string_pretty(&o)
+            .map_err(|e| format!("could not serialize archive: {e}"))?;
+        fs::write(&path, j).map_err(|e| format!("could not write {}: {e}", path.display()))?;
+        rewritten += 1;
     }
+    Ok(rewritten)
+}
 
-    result
+/// Rename every habit called `old` in one archive file's habits, returning
+/// whether any was.
+fn rename_in(habits: &mut [serde_json::Value], old: &str, new: &str) -> bool {
+    let mut renamed = false;
+    for habit in habits {
+        if habit.get("name").and_then(|n| n.as_str()) == Some(old) {
+            habit["name"] = serde_json::json!(new);
+            renamed = true;
+        }
+    }
+    renamed
 }
 
 /// The reached-goal dates recorded in one archived habit's JSON blob.
 ///
 /// Pure so it can be unit-tested without touching the filesystem. Daily habits
-/// judge each day against the goal; weekly `Count`/`Float` habits aggregate
-/// their recorded days into Mon–Sun weeks (within this one archive month) and
-/// mark every recorded day of a week that met its goal. A goal of `0` means
-/// "just track it", so every recorded day/week counts.
-fn archived_reached_dates(habit: &serde_json::Value) -> Vec<NaiveDate> {
+/// judge each day against the goal; weekly and monthly `Count`/`Float`/
+/// `Duration` habits aggregate their recorded days into Mon–Sun weeks (within
+/// this one archive month) or the whole month and mark every recorded day of a
+/// period that met its goal. A goal of `0` means "just track it", so every
+/// recorded day/period counts. `Addiction` habits mark
+/// every day of the archived month that stayed within the limit, including
+/// days with no entry. `Cycle` habits mark the days left in a success state,
+/// and `Rating` habits every rated day.
+pub fn archived_reached_dates(habit: &serde_json::Value) -> Vec<NaiveDate> {
     let habit_type = habit.get("type").and_then(|t| t.as_str()).unwrap_or("");
     let goal = habit.get("goal");
-    let period = habit
-        .get("period")
-        .and_then(|p| p.as_str())
-        .unwrap_or("Daily");
+    let field = |name: &str| habit.get(name).cloned().unwrap_or_default();
+    let period: GoalPeriod = serde_json::from_value(field("period")).unwrap_or_default();
+    let history: GoalHistory = serde_json::from_value(field("goal_history")).unwrap_or_default();
     let stats = match habit.get("stats").and_then(|s| s.as_object()) {
         Some(s) => s,
         None => return Vec::new(),
@@ -230,18 +388,18 @@ fn archived_reached_dates(habit: &serde_json::Value) -> Vec<NaiveDate> {
 
     // Numeric goal and per-day contribution, shared by the daily and weekly
     // branches. Bit habits carry no numeric goal and are always daily.
-    let numeric_goal: u64 = match habit_type {
-        "Count" => goal.and_then(|g| g.as_u64()).unwrap_or(0),
+    let numeric_goal = match habit_type {
+        "Count" | "Addiction" | "Duration" => goal.and_then(|g| g.as_u64()).unwrap_or(0),
         "Float" => goal
             .and_then(|g| g.as_object())
             .and_then(|o| o.get("value"))
             .and_then(|v| v.as_u64())
             .unwrap_or(0),
         _ => 0,
-    };
+    } as u32;
     let day_value = |value: &serde_json::Value| -> u64 {
         match habit_type {
-            "Count" => value.as_u64().unwrap_or(0),
+            "Count" | "Addiction" | "Duration" => value.as_u64().unwrap_or(0),
             "Float" => value
                 .as_object()
                 .and_then(|o| o.get("value"))
@@ -251,37 +409,79 @@ fn archived_reached_dates(habit: &serde_json::Value) -> Vec<NaiveDate> {
         }
     };
 
+    // the goal in force on a date, and the period it is judged over: weekly
+    // and monthly numeric goals are judged per period, not per day
+    let goal_on = |date: NaiveDate| -> (u64, GoalPeriod) {
+        let (goal, period) = history.at(date, (numeric_goal, period));
+        match habit_type {
+            "Count" | "Float" | "Duration" => (goal as u64, period),
+            _ => (goal as u64, GoalPeriod::Daily),
+        }
+    };
+
     let parse = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
     let mut out = Vec::new();
 
-    if period == "Weekly" && (habit_type == "Count" || habit_type == "Float") {
-        let mut weeks: HashMap<NaiveDate, (u64, Vec<NaiveDate>)> = HashMap::new();
+    if habit_type == "Addiction" {
+        let mut over_limit = HashSet::new();
+        let mut month = None;
         for (date_str, value) in stats {
             if let Some(date) = parse(date_str) {
-                let entry = weeks.entry(week_bounds(date).0).or_default();
-                entry.0 += day_value(value);
-                entry.1.push(date);
+                month = month.or(date.with_day(1));
+                if day_value(value) > goal_on(date).0 {
+                    over_limit.insert(date);
+                }
             }
         }
-        for (_monday, (sum, days)) in weeks {
-            if numeric_goal == 0 || sum >= numeric_goal {
-                out.extend(days);
+        let mut day = match month {
+            Some(m) => m,
+            None => return out,
+        };
+        while day.month() == month.map_or(0, |m| m.month()) {
+            if !over_limit.contains(&day) {
+                out.push(day);
             }
-        }
-    } else {
-        for (date_str, value) in stats {
-            let Some(date) = parse(date_str) else {
-                continue;
-            };
-            let reached = match habit_type {
-                "Bit" => value.as_bool().unwrap_or(false),
-                "Count" | "Float" => numeric_goal == 0 || day_value(value) >= numeric_goal,
-                _ => false,
+            day = match day.checked_add_days(Days::new(1)) {
+                Some(d) => d,
+                None => break,
             };
-            if reached {
-                out.push(date);
-            }
         }
+        return out;
+    }
+
+    let mut periods: HashMap<(NaiveDate, GoalPeriod), (u64, Vec<NaiveDate>)> = HashMap::new();
+    for (date_str, value) in stats {
+        let Some(date) = parse(date_str) else {
+            continue;
+        };
+        let (goal, period) = goal_on(date);
+        if period != GoalPeriod::Daily {
+            let entry = periods
+                .entry((period_bounds(date, period).0, period))
+                .or_default();
+            entry.0 += day_value(value);
+            entry.1.push(date);
+            continue;
+        }
+        let reached = match habit_type {
+            "Bit" => value.as_bool().unwrap_or(false),
+            "Rating" => true,
+            "Cycle" => habit
+                .get("successes")
+                .and_then(|s| s.as_array())
+                .is_some_and(|successes| successes.contains(value)),
+            "Count" | "Float" | "Duration" => goal == 0 || day_value(value) >= goal,
+            _ => false,
+        };
+        if reached {
+            out.push(date);
+        }
+    }
+    for (sum, days) in periods.into_values() {
+        out.extend(days.into_iter().filter(|&day| {
+            let goal = goal_on(day).0;
+            goal == 0 || sum >= goal
+        }));
     }
 
     out
@@ -300,6 +500,21 @@ mod tests {
         archived_reached_dates(&habit).into_iter().collect()
     }
 
+    #[test]
+    fn colours_are_written_as_hex() {
+        assert_eq!(hex(Color::Dark(BaseColor::Cyan)).unwrap(), "#00CDCD");
+        assert_eq!(hex(Color::parse("#1a2b3c").unwrap()).unwrap(), "#1A2B3C");
+        assert_eq!(hex(Color::TerminalDefault), None);
+    }
+
+    #[test]
+    fn due_label_reads_naturally() {
+        assert_eq!(due_label(0), "due today");
+        assert_eq!(due_label(1), "due in 1 day");
+        assert_eq!(due_label(3), "due in 3 days");
+        assert_eq!(due_label(-2), "overdue by 2 days");
+    }
+
     #[test]
     fn week_bounds_wraps_a_monday_to_sunday_span() {
         // 2024-01-03 is a Wednesday.
@@ -338,6 +553,88 @@ mod tests {
         assert_eq!(got, HashSet::from([d(1), d(3), d(5)]));
     }
 
+    #[test]
+    fn addiction_marks_every_day_of_the_month_within_the_limit() {
+        let got = reached(json!({
+            "type": "Addiction", "name": "coffee", "goal": 2,
+            "stats": { "2024-01-02": 2, "2024-01-03": 3 },
+        }));
+        assert_eq!(got.len(), 30, "31 days of January minus one over the limit");
+        assert!(got.contains(&d(1)), "an empty day is a clean day");
+        assert!(got.contains(&d(2)));
+        assert!(!got.contains(&d(3)));
+    }
+
+    #[test]
+    fn weekly_duration_sums_minutes_across_the_week() {
+        let got = reached(json!({
+            "type": "Duration", "name": "read", "goal": 90, "period": "Weekly",
+            "stats": { "2024-01-01": 45, "2024-01-02": 45, "2024-01-08": 30 },
+        }));
+        assert_eq!(got, HashSet::from([d(1), d(2)]));
+    }
+
+    #[test]
+    fn monthly_count_sums_the_whole_archived_month() {
+        let got = reached(json!({
+            "type": "Count", "name": "long run", "goal": 4, "period": "Monthly",
+            "stats": { "2024-01-01": 1, "2024-01-10": 1, "2024-01-20": 1, "2024-01-31": 1 },
+        }));
+        assert_eq!(got, HashSet::from([d(1), d(10), d(20), d(31)]));
+
+        let short = reached(json!({
+            "type": "Count", "name": "long run", "goal": 4, "period": "Monthly",
+            "stats": { "2024-01-01": 3 },
+        }));
+        assert!(short.is_empty());
+    }
+
+    #[test]
+    fn archived_days_use_the_goal_in_force_then() {
+        let got = reached(json!({
+            "type": "Count", "name": "pushups", "goal": 30,
+            "goal_history": [{ "until": "2024-01-09", "goal": 20, "period": "Daily" }],
+            "stats": { "2024-01-05": 20, "2024-01-12": 20, "2024-01-13": 30 },
+        }));
+        assert_eq!(got, HashSet::from([d(5), d(13)]));
+
+        let switched = reached(json!({
+            "type": "Count", "name": "gym", "goal": 2, "period": "Weekly",
+            "goal_history": [{ "until": "2024-01-07", "goal": 1, "period": "Daily" }],
+            "stats": { "2024-01-01": 1, "2024-01-08": 1, "2024-01-15": 1, "2024-01-16": 1 },
+        }));
+        assert_eq!(switched, HashSet::from([d(1), d(15), d(16)]));
+    }
+
+    #[test]
+    fn rename_only_touches_the_named_habit() {
+        let mut habits = vec![
+            json!({ "type": "Count", "name": "gym", "goal": 3, "stats": {} }),
+            json!({ "type": "Bit", "name": "read", "goal": true, "stats": {} }),
+        ];
+        assert!(rename_in(&mut habits, "gym", "lifting"));
+        assert_eq!(habits[0]["name"], "lifting");
+        assert_eq!(habits[1]["name"], "read");
+        assert!(!rename_in(&mut habits, "gym", "lifting"));
+    }
+
+    #[test]
+    fn month_bounds_handle_short_and_leap_months() {
+        let date = |m, day| NaiveDate::from_ymd_opt(2024, m, day).unwrap();
+        assert_eq!(month_bounds(date(2, 10)), (date(2, 1), date(2, 29)));
+        assert_eq!(month_bounds(date(12, 31)), (date(12, 1), date(12, 31)));
+    }
+
+    #[test]
+    fn cycle_marks_days_in_a_success_state() {
+        let got = reached(json!({
+            "type": "Cycle", "name": "sleep",
+            "states": ["bad", "ok", "good"], "successes": ["ok", "good"],
+            "stats": { "2024-01-01": "bad", "2024-01-02": "ok", "2024-01-03": "good" },
+        }));
+        assert_eq!(got, HashSet::from([d(2), d(3)]));
+    }
+
     #[test]
     fn a_zero_goal_counts_every_recorded_day() {
         let got = reached(json!({
@@ -7,10 +7,11 @@ use cursive::{Printer, Vec2};
 use chrono::prelude::*;
 use chrono::{Days, Local, NaiveDate};
 
-use crate::habit::{Bit, Count, Float, GoalPeriod, Habit, TrackEvent, ViewMode};
-use crate::stats::habit_stats;
+use crate::habit::{
+    Addiction, Bit, Count, Cycle, Duration, Float, GoalPeriod, Habit, Rating, TrackEvent, ViewMode,
+};
 use crate::theme::cursor_bg;
-use crate::utils::{VIEW_HEIGHT, VIEW_WIDTH};
+use crate::utils::{VIEW_HEIGHT, VIEW_WIDTH, due_label};
 
 use crate::CONFIGURATION;
 
@@ -34,6 +35,17 @@ where
         let year = now.year();
         let month = now.month();
 
+        // an every-N-days habit is due by its last completion, so work out the
+        // due days of the years any view draws at once instead of cell by cell
+        let due_dates = NaiveDate::from_ymd_opt(year - 3, 1, 1)
+            .zip(NaiveDate::from_ymd_opt(year, 12, 31))
+            .filter(|_| self.schedule().interval().is_some())
+            .map(|(from, to)| (from..=to, self.due_dates(from, to)));
+        let is_due = |d: NaiveDate| match &due_dates {
+            Some((span, due)) if span.contains(&d) => due.contains(&d),
+            _ => self.is_due(d),
+        };
+
         let goal_reached_style = Style::from(CONFIGURATION.reached_color());
         let future_style = Style::from(CONFIGURATION.inactive_color());
 
@@ -64,8 +76,23 @@ where
 
         // Only the first line of the description sits dimmed under the name,
         // truncated to the cell width; `i` opens the full text in a popup. The
-        // Heatmap view already draws on row 1, so it is skipped there.
-        if self.inner_data_ref().view_mode() != ViewMode::Heatmap && !self.description().is_empty()
+        // Heatmap view already draws on row 1, so it is skipped there. An
+        // every-N-days habit shows when it is next due there instead.
+        let due_in = self.days_until_due(Local::now().date_naive());
+        if let (Some(days), true) = (
+            due_in,
+            self.inner_data_ref().view_mode() != ViewMode::Heatmap,
+        ) {
+            let style = if days <= 0 {
+                Style::from(CONFIGURATION.todo_color())
+            } else {
+                future_style
+            };
+            printer.with_style(style, |p| {
+                p.print((0, 1), &format!(" {} ", due_label(days)))
+            });
+        } else if self.inner_data_ref().view_mode() != ViewMode::Heatmap
+            && !self.description().is_empty()
         {
             let first_line = self.description().lines().next().unwrap_or("");
             printer.with_style(future_style, |p| {
@@ -76,23 +103,30 @@ where
             });
         }
 
-        let weekly_goal_habit = self.period() == GoalPeriod::Weekly;
+        let period = self.period();
         let draw_week = |printer: &Printer| {
             let days = (1..31)
                 .filter_map(|i| NaiveDate::from_ymd_opt(year, month, i)) // dates 28-31 may not exist, ignore them if they don't
                 .collect::<Vec<_>>();
-            for (week, line_nr) in days.chunks(7).zip(2..) {
+            // a monthly goal has no weekly share, so its month is a single bar
+            let chunk = if period == GoalPeriod::Monthly {
+                days.len()
+            } else {
+                7
+            };
+            for (week, line_nr) in days.chunks(chunk).zip(2..) {
                 let is_this_week = week.contains(&Local::now().date_naive());
-                // For a weekly-goal habit the target lives on the week itself, so
-                // read the week's progress off any of its days rather than
-                // summing daily targets.
-                let (weekly_goal, completions) = if weekly_goal_habit {
-                    let goal = self.goal();
+                // For a weekly- or monthly-goal habit the target lives on the
+                // period itself, so read its progress off any of its days
+                // rather than summing daily targets.
+                let (weekly_goal, completions) = if period != GoalPeriod::Daily {
+                    let goal = self.goal_at(week[0]);
                     (goal, goal.saturating_sub(self.remaining(week[0])))
                 } else {
-                    let goal = self.goal() * week.len() as u32;
-                    let remaining = week.iter().map(|&i| self.remaining(i)).sum::<u32>();
-                    (goal, goal - remaining)
+                    let due: Vec<NaiveDate> = week.iter().copied().filter(|&d| is_due(d)).collect();
+                    let goal = due.iter().map(|&d| self.goal_at(d)).sum::<u32>();
+                    let remaining = due.iter().map(|&i| self.remaining(i)).sum::<u32>();
+                    (goal, goal.saturating_sub(remaining))
                 };
                 let full = VIEW_WIDTH - 8;
                 let bars_to_fill = (completions * full as u32)
@@ -131,7 +165,13 @@ where
                 let ts = ColorStyle::front(CONFIGURATION.todo_color());
                 let cs = ColorStyle::back(cursor_bg());
 
-                if self.reached_goal(d) {
+                // days off the habit's schedule are drawn as neutral
+                let due = is_due(d);
+                if !due {
+                    day_style = day_style.combine(future_style);
+                } else if let Some(color) = self.entry_color(d) {
+                    day_style = day_style.combine(Style::from(ColorStyle::front(color)));
+                } else if self.reached_goal(d) {
                     day_style = day_style.combine(Style::from(grs));
                 } else {
                     day_style = day_style.combine(Style::from(ts));
@@ -140,12 +180,21 @@ where
                     day_style = day_style.combine(cs);
                     fs = fs.combine(cs);
                 }
+                // days carrying a note are underlined
+                if self.notes().contains_key(&d) {
+                    day_style = day_style.combine(Effect::Underline);
+                    fs = fs.combine(Effect::Underline);
+                }
                 let coords: Vec2 = ((i % 7) * 3, i / 7 + 2).into();
                 if let Some(c) = self.get_by_date(d) {
                     printer.with_style(day_style, |p| {
                         p.print(coords, &format!("{c:^3}"));
                     });
-                } else if d < now {
+                } else if self.is_paused(d) {
+                    printer.with_style(fs, |p| {
+                        p.print(coords, &format!("{:^3}", CONFIGURATION.look.paused_chr));
+                    });
+                } else if d < now && due {
                     printer.with_style(fs, |p| {
                         p.print(coords, &format!("{:^3}", CONFIGURATION.look.missing_chr));
                     });
@@ -159,8 +208,27 @@ where
         };
 
         let archived = &self.inner_data_ref().archived_reached;
+        let paused_glyph = CONFIGURATION.look.paused_chr.to_string();
         let reached_or_archived =
             |date: NaiveDate| -> bool { self.reached_goal(date) || archived.contains(&date) };
+        // a day counts toward a percentage if it was due, or done anyway
+        let counts = |date: NaiveDate| -> bool { is_due(date) || reached_or_archived(date) };
+        // progress toward a monthly goal, as a percentage of it; an archived
+        // month only remembers whether it was reached
+        let month_progress = |first: NaiveDate| -> u32 {
+            let days = (0..31).filter_map(|i| first.checked_add_days(Days::new(i)));
+            if days
+                .take_while(|d| d.month() == first.month())
+                .any(reached_or_archived)
+            {
+                100
+            } else {
+                let goal = self.goal_at(first);
+                (goal.saturating_sub(self.remaining(first)) * 100)
+                    .checked_div(goal)
+                    .unwrap_or(0)
+            }
+        };
 
         let draw_month = |printer: &Printer| {
             let today = Local::now().date_naive();
@@ -176,13 +244,24 @@ where
 
                 let mut total_days = 0u32;
                 let mut reached_days = 0u32;
-                for day in 1..=31 {
-                    if let Some(date) = NaiveDate::from_ymd_opt(year, month_num, day)
-                        && date <= today
+                if period == GoalPeriod::Monthly {
+                    // one goal per month: show how far along it got
+                    if let Some(first) = NaiveDate::from_ymd_opt(year, month_num, 1)
+                        && first <= today
                     {
-                        total_days += 1;
-                        if reached_or_archived(date) {
-                            reached_days += 1;
+                        total_days = 100;
+                        reached_days = month_progress(first);
+                    }
+                } else {
+                    for day in 1..=31 {
+                        if let Some(date) = NaiveDate::from_ymd_opt(year, month_num, day)
+                            && date <= today
+                            && counts(date)
+                        {
+                            total_days += 1;
+                            if reached_or_archived(date) {
+                                reached_days += 1;
+                            }
                         }
                     }
                 }
@@ -221,9 +300,22 @@ where
                 let mut total_days = 0u32;
                 let mut reached_days = 0u32;
                 for m in 1..=12 {
+                    if period == GoalPeriod::Monthly {
+                        // count whole months rather than days
+                        if let Some(first) = NaiveDate::from_ymd_opt(y, m, 1)
+                            && first <= today
+                        {
+                            total_days += 1;
+                            if month_progress(first) >= 100 {
+                                reached_days += 1;
+                            }
+                        }
+                        continue;
+                    }
                     for day in 1..=31 {
                         if let Some(date) = NaiveDate::from_ymd_opt(y, m, day)
                             && date <= today
+                            && counts(date)
                         {
                             total_days += 1;
                             if reached_or_archived(date) {
@@ -259,29 +351,33 @@ where
         // A compact one-row month view: each day of the month is a single bar
         // whose height (`▁`..`█`) tracks that day's completion ratio. Reached
         // days are full and coloured as such, partial days sit mid-ramp, missed
-        // days show the lowest bar, and future days are left blank.
+        // days show the lowest bar, and future or unscheduled days are left
+        // blank.
         let draw_sparkline = |printer: &Printer| {
             const RAMP: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
             let today = Local::now().date_naive();
             let todo_style = Style::from(CONFIGURATION.todo_color());
             let cursor_style = Style::from(ColorStyle::back(cursor_bg()));
-            let goal = self.goal().max(1);
 
             let mut i = 0u32;
             while let Some(d) = NaiveDate::from_ymd_opt(year, month, i + 1) {
                 let coords: Vec2 = (i as usize, 2).into();
-                let (glyph, mut style): (&str, Style) = if d > today {
-                    (" ", future_style)
-                } else if self.reached_goal(d) {
-                    (RAMP[RAMP.len() - 1], goal_reached_style)
-                } else if self.get_by_date(d).is_some() {
-                    let done = goal.saturating_sub(self.remaining(d));
-                    let idx =
-                        ((done as f64 / goal as f64) * (RAMP.len() - 1) as f64).round() as usize;
-                    (RAMP[idx.min(RAMP.len() - 1)], todo_style)
-                } else {
-                    (RAMP[0], future_style)
-                };
+                let (glyph, mut style): (&str, Style) =
+                    if d <= today && self.is_paused(d) && !self.reached_goal(d) {
+                        (&paused_glyph, future_style)
+                    } else if d > today || (!is_due(d) && !self.reached_goal(d)) {
+                        (" ", future_style)
+                    } else if self.reached_goal(d) {
+                        (RAMP[RAMP.len() - 1], goal_reached_style)
+                    } else if self.get_by_date(d).is_some() {
+                        let goal = self.goal_at(d).max(1);
+                        let done = goal.saturating_sub(self.remaining(d));
+                        let idx = ((done as f64 / goal as f64) * (RAMP.len() - 1) as f64).round()
+                            as usize;
+                        (RAMP[idx.min(RAMP.len() - 1)], todo_style)
+                    } else {
+                        (RAMP[0], future_style)
+                    };
                 if d == now && printer.focused {
                     style = style.combine(cursor_style);
                 }
@@ -293,17 +389,14 @@ where
         let draw_stats = |printer: &Printer| {
             let today = Local::now().date_naive();
 
-            // every day this habit reached its goal, including months that
-            // have since been archived out of the live record
-            let reached: Vec<NaiveDate> = self
-                .get_dates()
-                .into_iter()
-                .filter(|&d| self.reached_goal(d))
-                .chain(archived.iter().copied())
-                .collect();
-
-            let s = habit_stats(&reached, today, self.period());
-            let unit = if weekly_goal_habit { "weeks" } else { "days" };
+            let s = self.stats_between(None, today);
+            // an every-N-days streak counts completions that came on time
+            let unit = match period {
+                _ if self.schedule().interval().is_some() => "intervals",
+                GoalPeriod::Daily => "days",
+                GoalPeriod::Weekly => "weeks",
+                GoalPeriod::Monthly => "months",
+            };
 
             let lines = [
                 format!("Current  {:>4} {unit}", s.current_streak),
@@ -315,7 +408,12 @@ where
                 ),
                 format!("Rate     {:>4} %", s.completion_rate),
             ];
-            for (i, line) in lines.iter().enumerate() {
+            // freezes left in the current allowance period, if any
+            let freezes = s
+                .freezes_left
+                .zip(self.freezes())
+                .map(|(left, f)| format!("Freezes  {left:>4} left/{}", f.per));
+            for (i, line) in lines.iter().chain(freezes.iter()).enumerate() {
                 let style = if i == 0 && s.current_streak > 0 {
                     goal_reached_style
                 } else {
@@ -354,7 +452,11 @@ where
                     let coords: Vec2 = (col as usize, row as usize + 1).into();
                     let (style, glyph): (Style, &str) = if reached_or_archived(date) {
                         (goal_reached_style, "█")
-                    } else if self.goal() > 0 && self.remaining(date) < self.goal() {
+                    } else if self.is_paused(date) {
+                        (future_style, &paused_glyph)
+                    } else if !is_due(date) {
+                        continue; // unscheduled days stay blank
+                    } else if self.goal_at(date) > 0 && self.remaining(date) < self.goal_at(date) {
                         (todo_style, "▒") // some progress, goal not met
                     } else {
                         (future_style, "░") // missed or no data
@@ -426,4 +528,4 @@ macro_rules! generate_view_impls {
     );
 }
 
-generate_view_impls!(Count, Bit, Float);
+generate_view_impls!(Count, Bit, Float, Addiction, Duration, Cycle, Rating);